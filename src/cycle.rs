@@ -0,0 +1,90 @@
+//! Cycle detection for iterated state machines, i.e. sequences `x0, f(x0), f(f(x0)), ...`.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cycle {
+    /// index of the first state which is part of the cycle
+    pub start: usize,
+    pub period: usize,
+}
+
+/// Brent's algorithm, only ever keeps two states around. Loops forever if the sequence never
+/// repeats.
+pub fn detect<T, F>(initial: &T, step: F) -> Cycle
+where
+    T: Clone + PartialEq,
+    F: Fn(&T) -> T,
+{
+    // find the period by teleporting the tortoise every power of two
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // hare runs one period ahead, they meet at the start of the cycle
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// The state after `n` steps, skipping over all the full cycles on the way.
+pub fn nth<T, F>(initial: T, step: F, n: usize) -> T
+where
+    T: Clone + PartialEq,
+    F: Fn(&T) -> T,
+{
+    let Cycle { start, period } = detect(&initial, &step);
+    let steps = if n < start {
+        n
+    } else {
+        start + (n - start) % period
+    };
+
+    (0..steps).fold(initial, |state, _| step(&state))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_cycle() {
+        // 0 -> 3 -> 4 -> 5 -> 6 -> 2 -> 3 ...
+        let step = |x: &u32| match x {
+            0 => 3,
+            6 => 2,
+            x => x + 1,
+        };
+        assert_eq!(
+            detect(&0, step),
+            Cycle {
+                start: 1,
+                period: 5
+            }
+        );
+        assert_eq!(nth(0, step, 0), 0);
+        assert_eq!(nth(0, step, 5), 2);
+        assert_eq!(
+            nth(0, step, 1_000_000_000),
+            step(&nth(0, step, 999_999_999))
+        );
+    }
+}
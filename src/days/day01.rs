@@ -1,51 +1,234 @@
-fn first_last_dig(line: &str) -> u32 {
-    let mut first_it = line.chars();
-    let mut last_it = line.chars().rev();
-    let mut first: Option<u32> = None;
-    let mut last: Option<u32> = None;
-
-    fn advance_number<T>(iterator: &mut T) -> Option<u32>
-    where
-        T: Iterator<Item = char>,
-    {
-        if let Some(number) = iterator.next() {
-            if number.is_ascii_digit() {
-                return number.to_digit(10);
+use std::fmt;
+
+use itertools::Itertools;
+
+const DIGITS: [(&str, u32); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+const SPELLED_DIGITS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+#[derive(Default)]
+struct Node {
+    children: Vec<(u8, usize)>,
+    value: Option<u32>,
+}
+
+struct Trie(Vec<Node>);
+
+impl Trie {
+    fn new() -> Self {
+        Self(vec![Node::default()])
+    }
+
+    fn insert(&mut self, word: impl Iterator<Item = u8>, value: u32) {
+        let mut curr = 0;
+        for byte in word {
+            curr = match self.0[curr].children.iter().find(|(chr, _)| *chr == byte) {
+                Some((_, next)) => *next,
+                None => {
+                    self.0.push(Node::default());
+                    let next = self.0.len() - 1;
+                    self.0[curr].children.push((byte, next));
+                    next
+                }
+            };
+        }
+        self.0[curr].value = Some(value);
+    }
+
+    // value and length of the shortest word which `bytes` starts with
+    fn match_start<'a>(&self, bytes: impl Iterator<Item = &'a u8>) -> Option<(u32, usize)> {
+        let mut curr = 0;
+        for (len, byte) in bytes.enumerate() {
+            curr = self.0[curr].children.iter().find(|(chr, _)| chr == byte)?.1;
+            if let Some(value) = self.0[curr].value {
+                return Some((value, len + 1));
             }
-            return None;
         }
         None
     }
+}
 
-    // Basic two pointers approach
-    while first.is_none() || last.is_none() {
-        if first.is_none() {
-            first = advance_number(&mut first_it);
+#[derive(Debug, Clone, Copy)]
+struct Match {
+    value: u32,
+    // byte offset of the token in the line
+    start: usize,
+    len: usize,
+}
+
+// Tokens are matched from the front with one trie and from the back with a trie of the reversed
+// tokens, so overlapping words like "eightwo" resolve to 8 and 2 without touching the input
+struct Scanner {
+    forward: Trie,
+    backward: Trie,
+}
+
+impl Scanner {
+    fn new<'a>(vocabulary: impl IntoIterator<Item = &'a (&'a str, u32)>) -> Self {
+        let mut forward = Trie::new();
+        let mut backward = Trie::new();
+        for (token, value) in vocabulary {
+            forward.insert(token.bytes(), *value);
+            backward.insert(token.bytes().rev(), *value);
         }
-        if last.is_none() {
-            last = advance_number(&mut last_it);
+        Self { forward, backward }
+    }
+
+    fn first(&self, line: &[u8]) -> Option<Match> {
+        (0..line.len()).find_map(|start| {
+            let (value, len) = self.forward.match_start(line[start..].iter())?;
+            Some(Match { value, start, len })
+        })
+    }
+
+    fn last(&self, line: &[u8]) -> Option<Match> {
+        (1..=line.len()).rev().find_map(|end| {
+            let (value, len) = self.backward.match_start(line[..end].iter().rev())?;
+            Some(Match {
+                value,
+                start: end - len,
+                len,
+            })
+        })
+    }
+}
+
+fn calibration_sum(input: &[u8], scanner: &Scanner) -> u32 {
+    input
+        .split(|byte| *byte == b'\n')
+        .filter_map(|line| first_last_dig(line, scanner))
+        .sum()
+}
+
+struct Calibration<'a> {
+    line: &'a [u8],
+    first: Option<Match>,
+    last: Option<Match>,
+}
+
+impl<'a> Calibration<'a> {
+    fn new(line: &'a [u8], scanner: &Scanner) -> Self {
+        Self {
+            line,
+            first: scanner.first(line),
+            last: scanner.last(line),
+        }
+    }
+
+    fn value(&self) -> Option<u32> {
+        Some(self.first?.value * 10 + self.last?.value)
+    }
+
+    fn fmt_token(&self, f: &mut fmt::Formatter<'_>, token: Option<Match>) -> fmt::Result {
+        match token {
+            Some(Match { start, len, .. }) => {
+                let text = String::from_utf8_lossy(&self.line[start..(start + len)]);
+                write!(f, "{text:?}@{start}")
+            }
+            None => write!(f, "-"),
+        }
+    }
+}
+
+impl fmt::Display for Calibration<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_token(f, self.first)?;
+        write!(f, " ")?;
+        self.fmt_token(f, self.last)?;
+        match self.value() {
+            Some(value) => write!(f, " => {value}"),
+            None => write!(f, " => -"),
         }
     }
-    first.unwrap() * 10 + last.unwrap()
 }
 
-pub fn main() {
-    let mut input: String = String::from(include_str!("../../input/day01"));
+fn first_last_dig(line: &[u8], scanner: &Scanner) -> Option<u32> {
+    Calibration::new(line, scanner).value()
+}
+
+/// Shows for every line which tokens got picked under both parts' rules and where they are.
+/// Lines where the parts disagree are marked with `!`.
+pub fn diagnose(input: &[u8], _args: &[String]) -> String {
+    let digits = Scanner::new(&DIGITS);
+    let with_spelled = Scanner::new(DIGITS.iter().chain(SPELLED_DIGITS.iter()));
+
+    input
+        .split(|byte| *byte == b'\n')
+        .enumerate()
+        .map(|(no, line)| {
+            let part1 = Calibration::new(line, &digits);
+            let part2 = Calibration::new(line, &with_spelled);
+            let marker = if part1.value() != part2.value() {
+                '!'
+            } else {
+                ' '
+            };
+            format!(
+                "{marker} line {}: {} | part 1: {part1} | part 2: {part2}",
+                no + 1,
+                String::from_utf8_lossy(line)
+            )
+        })
+        .join("\n")
+}
+
+pub fn main(input: &[u8]) -> String {
+    let digits = Scanner::new(&DIGITS);
+    let with_spelled = Scanner::new(DIGITS.iter().chain(SPELLED_DIGITS.iter()));
+
+    let p1 = calibration_sum(input, &digits);
+    let p2 = calibration_sum(input, &with_spelled);
+    format!("part 1: {p1}\npart 2: {p2}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overlapping_words() {
+        let scanner = Scanner::new(DIGITS.iter().chain(SPELLED_DIGITS.iter()));
+        assert_eq!(first_last_dig(b"eightwothree", &scanner), Some(83));
+        assert_eq!(first_last_dig(b"zoneight234", &scanner), Some(14));
+        assert_eq!(first_last_dig(b"xtwone3four", &scanner), Some(24));
+        assert_eq!(first_last_dig(b"oneight", &scanner), Some(18));
+        assert_eq!(first_last_dig(b"seven", &scanner), Some(77));
+        assert_eq!(first_last_dig(b"abc", &scanner), None);
+    }
+
+    #[test]
+    fn diagnose_lines() {
+        let scanner = Scanner::new(DIGITS.iter().chain(SPELLED_DIGITS.iter()));
+        let calibration = Calibration::new(b"xtwone3four", &scanner);
+        assert_eq!(calibration.to_string(), "\"two\"@1 \"four\"@7 => 24");
+        let nothing = Calibration::new(b"abc", &Scanner::new(&DIGITS));
+        assert_eq!(nothing.to_string(), "- - => -");
 
-    // Really bad but this problem is mid so w/e
-    let part2 = true;
-    if part2 {
-        input = input
-            .replace("one", "o1e")
-            .replace("two", "t2o")
-            .replace("three", "t3e")
-            .replace("four", "f4r")
-            .replace("five", "f5e")
-            .replace("six", "s6x")
-            .replace("seven", "s7n")
-            .replace("eight", "e8t")
-            .replace("nine", "n9e");
+        let report = diagnose(b"1abc2\ntwo1nine", &[]);
+        assert_eq!(
+            report,
+            "  line 1: 1abc2 | part 1: \"1\"@0 \"2\"@4 => 12 | part 2: \"1\"@0 \"2\"@4 => 12\n\
+             ! line 2: two1nine | part 1: \"1\"@3 \"1\"@3 => 11 | part 2: \"two\"@0 \"nine\"@4 => 29"
+        );
     }
-    let ans = input.lines().map(first_last_dig).sum::<u32>();
-    println!("{ans}");
 }
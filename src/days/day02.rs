@@ -1,73 +1,175 @@
-use std::cmp::max;
-struct Cube {
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::parse;
+
+const PART1_BAG: [(&str, u32); 3] = [("red", 12), ("green", 13), ("blue", 14)];
+
+/// Cubes per color, either in a bag or in a single handful.
+type Cubes<'a> = HashMap<&'a str, u32>;
+
+struct Game<'a> {
     id: u32,
-    red: u32,
-    green: u32,
-    blue: u32,
+    draws: Vec<Cubes<'a>>,
 }
 
-impl Cube {
-    fn satisfies_part1(&self) -> bool {
-        self.red <= 12 && self.green <= 13 && self.blue <= 14
+impl<'a> Game<'a> {
+    fn from_line(line: &'a str) -> Option<Self> {
+        let (game, draws) = line.split_once(':')?;
+        let id = parse::number(game.strip_prefix("Game ")?.as_bytes())?;
+
+        let draws = draws
+            .split(';')
+            .map(|draw| {
+                // the same color twice in one handful adds up
+                draw.split(',')
+                    .try_fold(Cubes::new(), |mut cubes, color_cubes| {
+                        let (count, color) = color_cubes.trim().split_once(' ')?;
+                        *cubes.entry(color).or_default() += parse::number::<u32>(count.as_bytes())?;
+                        Some(cubes)
+                    })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { id, draws })
     }
 
-    fn get_power(&self) -> u32 {
-        self.red * self.green * self.blue
+    fn is_possible_with(&self, bag: &Cubes) -> bool {
+        self.draws.iter().all(|draw| {
+            draw.iter()
+                .all(|(color, count)| bag.get(color).is_some_and(|max| count <= max))
+        })
     }
 
-    fn new(id: u32) -> Self {
-        Self {
-            id,
-            red: 0,
-            green: 0,
-            blue: 0,
+    /// The fewest cubes of each color which make every draw possible.
+    fn minimum_bag(&self) -> Cubes<'a> {
+        let mut bag = Cubes::new();
+        for (color, count) in self.draws.iter().flatten() {
+            let max = bag.entry(color).or_default();
+            *max = (*max).max(*count);
         }
+        bag
     }
 }
 
-fn parse_line(line: &str, no: u32) -> Cube {
-    let mut cube = Cube::new(no);
-    for (number, mut color) in line
-        .split(' ')
-        .skip(2)
-        .step_by(2)
-        .zip(line.split(' ').skip(3).step_by(2))
-    {
-        let number = number.parse::<u32>().unwrap();
-
-        if color.ends_with(';') || color.ends_with(',') {
-            color = &color[0..color.len() - 1];
-        }
+// product over the given colors, any of them missing from the bag needs no cubes, which makes
+// the power 0
+fn get_power<'a>(bag: &Cubes, colors: impl IntoIterator<Item = &'a str>) -> u32 {
+    colors
+        .into_iter()
+        .map(|color| bag.get(color).copied().unwrap_or(0))
+        .product()
+}
 
-        match color {
-            "red" => cube.red = max(cube.red, number),
-            "green" => cube.green = max(cube.green, number),
-            "blue" => cube.blue = max(cube.blue, number),
-            err => unreachable!(
-                "Invalid color supplied! Valid colors are red, green, blue. Supplied value: {err}"
-            ),
-        }
-    }
+// every color drawn in any of the games
+fn colors<'a>(games: &[Game<'a>]) -> Vec<&'a str> {
+    games
+        .iter()
+        .flat_map(|game| game.draws.iter().flat_map(|draw| draw.keys().copied()))
+        .unique()
+        .collect_vec()
+}
 
-    cube
+fn parse_games(input: &str) -> Vec<Game<'_>> {
+    input
+        .lines()
+        .map(|line| Game::from_line(line).unwrap())
+        .collect_vec()
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day02");
+fn possible_ids_sum(games: &[Game], bag: &Cubes) -> u32 {
+    games
+        .iter()
+        .filter(|game| game.is_possible_with(bag))
+        .map(|game| game.id)
+        .sum()
+}
 
-    let cubes = input
-        .lines()
-        .enumerate()
-        .map(|(no, line)| parse_line(line, (no + 1) as u32))
-        .collect::<Vec<_>>();
+/// Which games are possible with the bag given as `<color>=<count>` arguments.
+pub fn feasible(input: &[u8], args: &[String]) -> String {
+    let games = parse_games(std::str::from_utf8(input).unwrap());
+    let bag: Cubes = args
+        .iter()
+        .map(|arg| {
+            let (color, count) = arg
+                .split_once('=')
+                .expect("Bag is given as <color>=<count>");
+            (color, parse::number(count.as_bytes()).unwrap())
+        })
+        .collect();
+
+    let ids = games
+        .iter()
+        .filter(|game| game.is_possible_with(&bag))
+        .map(|game| game.id)
+        .join(", ");
+    format!("possible: {ids}\nsum: {}", possible_ids_sum(&games, &bag))
+}
+
+pub fn main(input: &[u8]) -> String {
+    let games = parse_games(std::str::from_utf8(input).unwrap());
 
-    let part1_ans = cubes
+    let part1_ans = possible_ids_sum(&games, &Cubes::from(PART1_BAG));
+
+    let colors = colors(&games);
+    let part2_ans = games
         .iter()
-        .filter(|cube| cube.satisfies_part1())
-        .map(|cube| cube.id)
+        .map(|game| get_power(&game.minimum_bag(), colors.iter().copied()))
         .sum::<u32>();
 
-    let part2_ans = cubes.iter().map(|cube| cube.get_power()).sum::<u32>();
+    format!("part 1: {part1_ans} part 2: {part2_ans}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn example_games() {
+        assert_eq!(main(EXAMPLE), "part 1: 8 part 2: 2286");
+        assert_eq!(
+            feasible(
+                EXAMPLE,
+                &[
+                    "red=20".to_owned(),
+                    "green=13".to_owned(),
+                    "blue=6".to_owned()
+                ]
+            ),
+            "possible: 1, 2, 3, 5\nsum: 11"
+        );
+        // without any blue cubes only games which never draw blue are possible
+        assert_eq!(
+            feasible(EXAMPLE, &["red=20".to_owned(), "green=20".to_owned()]),
+            "possible: \nsum: 0"
+        );
+    }
+
+    #[test]
+    fn single_games() {
+        let game = Game::from_line("Game 7: 3 blue, 4 blue, 1 red; 2 red").unwrap();
+        assert_eq!(game.id, 7);
+        assert_eq!(game.draws[0]["blue"], 7);
+        let bag = game.minimum_bag();
+        assert_eq!((bag["blue"], bag["red"]), (7, 2));
+        assert!(game.is_possible_with(&Cubes::from([("blue", 7), ("red", 2)])));
+        assert!(!game.is_possible_with(&Cubes::from([("blue", 6), ("red", 2)])));
+        assert_eq!(get_power(&bag, ["blue", "red"]), 14);
+        // no green cubes drawn, so the bag needs none of them
+        assert_eq!(get_power(&bag, ["blue", "red", "green"]), 0);
 
-    println!("part 1: {part1_ans} part 2: {part2_ans}");
+        let games = parse_games("Game 1: 3 yellow, 4 red, 1 green, 2 blue");
+        let colors = colors(&games);
+        assert_eq!(get_power(&games[0].minimum_bag(), colors), 24);
+
+        assert!(Game::from_line("Game 1: three blue").is_none());
+        assert!(Game::from_line("Game x: 3 blue").is_none());
+    }
 }
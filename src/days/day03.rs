@@ -1,160 +1,170 @@
-use itertools::{Itertools, Position};
-
-fn part_numbers_sum(lines: &[&str]) -> u64 {
-    let mut sum: u64 = 0;
-
-    for (y, line) in lines.iter().enumerate() {
-        let mut last_idx: usize = 0;
-        let mut group_no: usize = 0;
-        for (_, group) in &line
-            .chars()
-            .enumerate()
-            .filter(|(_, chr)| chr.is_ascii_digit())
-            .map(|(x, chr)| (x, chr.to_digit(10).unwrap() as u64))
-            .group_by(|(x, _)| {
-                let same_group = *x == 0 || (x - 1) == last_idx;
-                last_idx = *x;
-                if !same_group {
-                    group_no += 1;
-                }
-                group_no
-            })
-        {
-            let mut is_valid = false;
-            let mut number = 0;
-            for (pos, (x, no)) in group.with_position() {
-                number *= 10;
-                number += no;
-                if !is_valid {
-                    let left = x.saturating_sub(1);
-                    let top = y.saturating_sub(1);
-                    match pos {
-                        Position::First => {
-                            is_valid |= is_symbol(line.chars().nth(left)) // left
-                                || is_symbol(lines.get(y + 1).and_then(|line| line.chars().nth(left))) // left-bottom
-                                || is_symbol(lines.get(top).and_then(|line| line.chars().nth(left)));
-                            // left-top
-                        }
-                        Position::Last => {
-                            is_valid |= is_symbol(line.chars().nth(x + 1)) // right
-                                || is_symbol(lines.get(y + 1).and_then(|line| line.chars().nth(x + 1))) // right-bottom
-                                || is_symbol(lines.get(top).and_then(|line| line.chars().nth(x + 1)));
-                            // right-top
-                        }
-                        Position::Only => {
-                            is_valid |= is_symbol(line.chars().nth(left)) // left
-                                || is_symbol(lines.get(y + 1).and_then(|line| line.chars().nth(left))) // left-bottom
-                                || is_symbol(lines.get(top).and_then(|line| line.chars().nth(left))) // left-top
-                                || is_symbol(line.chars().nth(x + 1)) // right
-                                || is_symbol(lines.get(y + 1).and_then(|line| line.chars().nth(x + 1))) // right-bottom
-                                || is_symbol(lines.get(top).and_then(|line| line.chars().nth(x + 1)));
-                            // right-top
-                        }
-                        _ => {}
-                    };
-                    is_valid |= is_symbol(lines.get(y + 1).and_then(|line| line.chars().nth(x))) // bottom
-                    || is_symbol(lines.get(top).and_then(|line| line.chars().nth(x)));
-                    // top
-                }
-                if pos == Position::Last || pos == Position::Only {
-                    if is_valid {
-                        sum += number;
-                    }
-                    number = 0;
-                    is_valid = false;
-                }
-            }
-        }
-    }
+use itertools::Itertools;
+
+use crate::parse;
 
-    sum
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Empty,
+    Number(usize),
+    Symbol(usize),
 }
 
-fn is_symbol(chr: Option<char>) -> bool {
-    if let Some(chr) = chr {
-        !chr.is_ascii_digit() && chr != '.'
-    } else {
-        false
-    }
+#[derive(Debug)]
+struct Number {
+    value: u64,
+    y: usize,
+    // x range of the digits, end exclusive
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug)]
+struct Symbol {
+    chr: u8,
+    x: usize,
+    y: usize,
+}
+
+/// Every number and symbol of the schematic, plus a grid pointing back at them so neighbours can
+/// be looked up directly.
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    cells: Vec<Cell>,
+    width: usize,
+    height: usize,
 }
 
-fn get_number(line: Option<&&str>, x: usize) -> Option<u64> {
-    let line = line?;
-    if line.chars().nth(x).unwrap_or('.').is_ascii_digit() {
-        let mut leftmost = x;
-        let mut number: u64 = 0;
-        while leftmost != 0
-            && line
-                .chars()
-                .nth(leftmost - 1)
-                .unwrap_or('.')
-                .is_ascii_digit()
-        {
-            leftmost -= 1;
+impl Schematic {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let lines = bytes.split(|byte| *byte == b'\n').collect_vec();
+        let width = lines.first().map_or(0, |line| line.len());
+        let height = lines.len();
+        // the cells are one flat grid, so a ragged line would spill into the next row
+        if let Some((no, line)) = lines.iter().find_position(|line| line.len() != width) {
+            return Err(format!(
+                "Line {} is {} wide while the first one is {width}",
+                no + 1,
+                line.len()
+            ));
         }
-        for idx in leftmost..(leftmost + 3) {
-            if let Some(digit) = line.chars().nth(idx).unwrap_or('.').to_digit(10) {
-                number *= 10;
-                number += digit as u64;
-            } else {
-                break;
+        let mut schematic = Self {
+            numbers: Vec::new(),
+            symbols: Vec::new(),
+            cells: vec![Cell::Empty; width * height],
+            width,
+            height,
+        };
+
+        for (y, line) in lines.iter().enumerate() {
+            let mut x = 0;
+            while x < line.len() {
+                let chr = line[x];
+                if chr.is_ascii_digit() {
+                    let start = x;
+                    while x < line.len() && line[x].is_ascii_digit() {
+                        schematic.cells[y * width + x] = Cell::Number(schematic.numbers.len());
+                        x += 1;
+                    }
+                    let value = parse::number(&line[start..x])
+                        .ok_or_else(|| format!("The number at ({start}, {y}) is too big"))?;
+                    schematic.numbers.push(Number {
+                        value,
+                        y,
+                        start,
+                        end: x,
+                    });
+                    continue;
+                }
+                if chr != b'.' {
+                    schematic.cells[y * width + x] = Cell::Symbol(schematic.symbols.len());
+                    schematic.symbols.push(Symbol { chr, x, y });
+                }
+                x += 1;
             }
         }
 
-        Some(number)
-    } else {
-        None
+        Ok(schematic)
     }
-}
-
-fn adjacent_numbers(schematic: &[&str], x: usize, y: usize) -> Vec<u64> {
-    let mut numbers = Vec::new();
-    let top = y.saturating_sub(1);
-    let left = x.saturating_sub(1);
 
-    // If only Rust has is_some_and...
+    // cells in the rectangle around the given span of a row, the span itself included
+    fn surrounding(&self, y: usize, start: usize, end: usize) -> impl Iterator<Item = Cell> + '_ {
+        let xs = start.saturating_sub(1)..(end + 1).min(self.width);
+        let ys = y.saturating_sub(1)..(y + 2).min(self.height);
+        ys.cartesian_product(xs)
+            .map(|(y, x)| self.cells[y * self.width + x])
+    }
 
-    let mut get_push_number = |y: usize, x: usize| -> bool {
-        if let Some(number) = get_number(schematic.get(y), x) {
-            numbers.push(number);
-            true
-        } else {
-            false
-        }
-    };
+    fn numbers_adjacent_to(&self, symbol: &Symbol) -> impl Iterator<Item = &Number> + '_ {
+        self.surrounding(symbol.y, symbol.x, symbol.x + 1)
+            .filter_map(|cell| match cell {
+                Cell::Number(idx) => Some(idx),
+                _ => None,
+            })
+            .unique()
+            .map(|idx| &self.numbers[idx])
+    }
 
-    if !get_push_number(top, x) {
-        get_push_number(top, x + 1);
-        get_push_number(top, left);
+    fn symbols_adjacent_to(&self, number: &Number) -> impl Iterator<Item = &Symbol> + '_ {
+        self.surrounding(number.y, number.start, number.end)
+            .filter_map(|cell| match cell {
+                Cell::Symbol(idx) => Some(&self.symbols[idx]),
+                _ => None,
+            })
     }
-    if !get_push_number(y + 1, x) {
-        get_push_number(y + 1, x + 1);
-        get_push_number(y + 1, left);
+
+    fn part_numbers_sum(&self) -> u64 {
+        self.numbers
+            .iter()
+            .filter(|number| self.symbols_adjacent_to(number).next().is_some())
+            .map(|number| number.value)
+            .sum()
     }
 
-    get_push_number(y, left);
-    get_push_number(y, x + 1);
+    /// Sum of the products of the numbers around every `gear` which touches exactly two numbers.
+    fn gears_sum(&self, gear: u8) -> u64 {
+        self.symbols
+            .iter()
+            .filter(|symbol| symbol.chr == gear)
+            .filter_map(|symbol| {
+                self.numbers_adjacent_to(symbol)
+                    .collect_tuple()
+                    .map(|(first, second): (&Number, &Number)| first.value * second.value)
+            })
+            .sum()
+    }
+}
 
-    numbers
+pub fn main(input: &[u8]) -> String {
+    let schematic = Schematic::from_bytes(input).unwrap_or_else(|err| panic!("{err}"));
+    format!(
+        "part 1: {}\npart 2: {}",
+        schematic.part_numbers_sum(),
+        schematic.gears_sum(b'*')
+    )
 }
 
-fn gears_sum(schematic: &[&str], gear: char) -> u64 {
-    let mut sum = 0;
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    for (y, line) in schematic.iter().enumerate() {
-        for (x, _) in line.chars().enumerate().filter(|(_, chr)| *chr == gear) {
-            let adj = adjacent_numbers(schematic, x, y);
-            if adj.len() == 2 {
-                sum += adj.iter().product::<u64>();
-            }
-        }
-    }
+    const EXAMPLE: &[u8] = b"467..114..\n...*......\n..35..633.\n......#...\n617*......\n\
+        .....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..";
 
-    sum
-}
+    #[test]
+    fn example_schematic() {
+        let schematic = Schematic::from_bytes(EXAMPLE).unwrap();
+        assert_eq!(schematic.part_numbers_sum(), 4361);
+        assert_eq!(schematic.gears_sum(b'*'), 467835);
+    }
 
-pub fn main() {
-    let input: Vec<_> = include_str!("../../input/day03").split('\n').collect();
-    println!("part 1: {}", part_numbers_sum(&input));
-    println!("part 2: {}", gears_sum(&input, '*'));
+    #[test]
+    fn ragged_lines() {
+        assert_eq!(
+            Schematic::from_bytes(b"467..\n...*..\n..35.").err(),
+            Some("Line 2 is 6 wide while the first one is 5".to_owned())
+        );
+        assert!(Schematic::from_bytes(b"467..\n...*.\n..35").is_err());
+        assert!(Schematic::from_bytes(b"99999999999999999999*").is_err());
+    }
 }
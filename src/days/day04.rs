@@ -1,48 +1,93 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 
-const CARD_OFFSET: usize = 10;
+use itertools::Itertools;
 
-fn point_worth(line: &str) -> Option<u32> {
-    let (winning, chosen) = line[CARD_OFFSET..].split_once('|')?;
+use crate::parse;
 
-    let mut winning: HashMap<&str, u8> = winning.split_whitespace().map(|no| (no, 0)).collect();
-    chosen.split_whitespace().for_each(|no| {
-        winning.entry(no).and_modify(|count| *count += 1);
-    });
+#[derive(Debug)]
+struct Card {
+    id: u32,
+    winning: HashSet<u32>,
+    chosen: HashSet<u32>,
+}
+
+impl Card {
+    // Card <id>: <winning numbers> | <chosen numbers>, any amount of padding in between
+    fn from_bytes(line: &[u8]) -> Option<Self> {
+        let (header, numbers) = parse::key_value(line)?;
+        let id = match parse::words(header).collect_tuple()? {
+            (b"Card", id) => parse::number(id)?,
+            _ => return None,
+        };
+        let (winning, chosen) = parse::split_once(numbers, b'|')?;
 
-    let sum: u32 = winning.into_values().sum::<u8>() as u32;
-    if sum == 0 {
-        None
-    } else {
-        Some(sum)
+        Some(Self {
+            id,
+            winning: parse::numbers(winning).collect::<Option<_>>()?,
+            chosen: parse::numbers(chosen).collect::<Option<_>>()?,
+        })
+    }
+
+    fn matches(&self) -> usize {
+        self.winning.intersection(&self.chosen).count()
     }
-}
 
-fn part2(input: &str) -> u32 {
-    let mut count: HashMap<usize, u32> = input.lines().enumerate().map(|(no, _)| (no, 1)).collect();
+    fn point_worth(&self) -> u32 {
+        match self.matches() {
+            0 => 0,
+            matches => 2u32.pow(matches as u32 - 1),
+        }
+    }
+}
 
-    for (idx, worth) in input
-        .lines()
-        .map(|line| point_worth(line).unwrap_or(0))
-        .enumerate()
-    {
-        let card_count = count.get(&idx).unwrap().to_owned();
+// Every card only ever wins copies of the cards after it, so one pass in order settles the counts
+fn part2(cards: &[Card]) -> u32 {
+    let mut count = vec![1u32; cards.len()];
 
-        ((idx + 1)..=(idx + worth as usize)).for_each(|idx| {
-            count.entry(idx).and_modify(|count| *count += card_count);
-        });
+    for (idx, card) in cards.iter().enumerate() {
+        let won = (idx + 1)..(idx + 1 + card.matches()).min(cards.len());
+        let card_count = count[idx];
+        count[won].iter_mut().for_each(|count| *count += card_count);
     }
 
-    count.into_values().sum::<u32>()
+    count.iter().sum()
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day04");
-    let part1 = input
-        .lines()
-        .filter_map(point_worth)
-        .map(|number| 2u32.pow(number - 1))
-        .sum::<u32>();
-    println!("part1: {part1}");
-    println!("part2: {}", part2(input));
+pub fn main(input: &[u8]) -> String {
+    let cards = input
+        .split(|byte| *byte == b'\n')
+        .map(|line| Card::from_bytes(line).unwrap())
+        .sorted_by_key(|card| card.id)
+        .collect_vec();
+
+    let part1 = cards.iter().map(Card::point_worth).sum::<u32>();
+    format!("part1: {part1}\npart2: {}", part2(&cards))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn example_cards() {
+        assert_eq!(main(EXAMPLE), "part1: 13\npart2: 30");
+    }
+
+    #[test]
+    fn padded_ids() {
+        let card = Card::from_bytes(b"Card  12:  1 21 | 21  4").unwrap();
+        assert_eq!(card.id, 12);
+        assert_eq!(card.point_worth(), 1);
+        let card = Card::from_bytes(b"Card 187: 5 | 6").unwrap();
+        assert_eq!((card.id, card.matches()), (187, 0));
+        assert!(Card::from_bytes(b"Cards 1: 5 | 6").is_none());
+        assert!(Card::from_bytes(b"Card 1: 5 x | 6").is_none());
+    }
 }
@@ -1,5 +1,11 @@
 use itertools::Itertools;
-use std::{error::Error, ops::Range};
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    ops::Range,
+};
+
+use crate::parse;
 
 #[derive(Debug, PartialEq, Eq)]
 struct ConvertMap {
@@ -9,16 +15,26 @@ struct ConvertMap {
 }
 
 impl ConvertMap {
-    fn from_string(source: &str) -> Result<Self, Box<dyn Error>> {
-        let parts: Vec<_> = source.split(' ').collect();
-        if parts.len() != 3 {
-            Err("Wrong Input! Expected: <dest_start> <source_start> <range>".into())
-        } else {
-            Ok(Self {
-                dest_start: parts[0].parse()?,
-                source_start: parts[1].parse()?,
-                range: parts[2].parse()?,
-            })
+    fn from_bytes(source: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut parts = parse::words(source).map(parse::number);
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Some(dest_start)), Some(Some(source_start)), Some(Some(range)), None) => {
+                let map = Self {
+                    dest_start,
+                    source_start,
+                    range,
+                };
+                // both ends staying inside the domain means no range arithmetic can overflow
+                let fits = |start: i64| {
+                    start >= DOMAIN.start && (0..=(DOMAIN.end - start)).contains(&range)
+                };
+                if fits(source_start) && fits(dest_start) {
+                    Ok(map)
+                } else {
+                    Err(format!("{map:?} reaches outside of {DOMAIN:?}").into())
+                }
+            }
+            _ => Err("Wrong Input! Expected: <dest_start> <source_start> <range>".into()),
         }
     }
 
@@ -30,84 +46,297 @@ impl ConvertMap {
             None
         }
     }
+
+    /// Splits `source` into the converted part covered by this map and the untouched parts
+    /// before and after it.
+    fn convert_range(&self, source: Range<i64>) -> (Option<Range<i64>>, [Option<Range<i64>>; 2]) {
+        let source_end = self.source_start + self.range;
+        let non_empty = |range: Range<i64>| (!range.is_empty()).then_some(range);
+
+        let before = non_empty(source.start..source.end.min(self.source_start));
+        let after = non_empty(source.start.max(source_end)..source.end);
+        let inside = non_empty(source.start.max(self.source_start)..source.end.min(source_end))
+            .map(|inside| {
+                let start = self.convert(inside.start).unwrap();
+                start..(start + (inside.end - inside.start))
+            });
+
+        (inside, [before, after])
+    }
+
+    fn offset(&self) -> i64 {
+        self.dest_start - self.source_start
+    }
 }
 
-fn parse_seeds(seeds: &str) -> Vec<i64> {
-    seeds
-        .split(' ')
-        .skip(1)
-        .map(|no| no.parse().unwrap())
-        .collect_vec()
+// Almanac numbers are never negative, which keeps every piece bounded
+const DOMAIN: Range<i64> = 0..i64::MAX;
+
+/// Maps every value in `source` to `value + offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Piece {
+    source: Range<i64>,
+    offset: i64,
 }
 
-fn parse_seeds_ranges(seeds: &str) -> Vec<Range<i64>> {
-    seeds
-        .split(' ')
-        .skip(1)
-        .chunks(2)
+impl Piece {
+    fn dest(&self) -> Range<i64> {
+        (self.source.start + self.offset)..(self.source.end + self.offset)
+    }
+}
+
+/// Any number of categories composed into one piecewise linear function over the whole
+/// domain.
+#[derive(Debug)]
+struct Mapping {
+    // sorted by source, covering DOMAIN without gaps
+    pieces: Vec<Piece>,
+    // the same pieces sorted by destination, for the inverse
+    by_dest: Vec<Piece>,
+}
+
+impl Mapping {
+    fn from_pieces(mut pieces: Vec<Piece>) -> Self {
+        pieces.sort_by_key(|piece| piece.source.start);
+        // neighbours shifted by the same offset are really a single piece
+        let pieces = pieces
+            .into_iter()
+            .coalesce(|prev, next| {
+                if prev.offset == next.offset && prev.source.end == next.source.start {
+                    Ok(Piece {
+                        source: prev.source.start..next.source.end,
+                        offset: prev.offset,
+                    })
+                } else {
+                    Err((prev, next))
+                }
+            })
+            .collect_vec();
+
+        let mut by_dest = pieces.clone();
+        by_dest.sort_by_key(|piece| piece.dest().start);
+        Self { pieces, by_dest }
+    }
+
+    fn compose(categories: &[&Category]) -> Self {
+        let identity = Self::from_pieces(vec![Piece {
+            source: DOMAIN,
+            offset: 0,
+        }]);
+        categories
+            .iter()
+            .fold(identity, |mapping, category| mapping.then(&category.maps))
+    }
+
+    // Every map of the category takes its share of the destination ranges, whatever is left over
+    // after the last map passes through unchanged
+    fn then(&self, category: &[ConvertMap]) -> Self {
+        let mut unconverted = self
+            .pieces
+            .iter()
+            .map(|piece| (piece.dest(), piece.offset))
+            .collect_vec();
+        let mut converted = Vec::with_capacity(unconverted.len());
+
+        for map in category {
+            let mut rest = Vec::with_capacity(unconverted.len());
+            for (dest, offset) in unconverted.drain(..) {
+                let (inside, outside) = map.convert_range(dest);
+                converted.extend(inside.map(|inside| (inside, offset + map.offset())));
+                rest.extend(outside.into_iter().flatten().map(|dest| (dest, offset)));
+            }
+            unconverted = rest;
+        }
+        converted.append(&mut unconverted);
+
+        Self::from_pieces(
+            converted
+                .into_iter()
+                .map(|(dest, offset)| Piece {
+                    source: (dest.start - offset)..(dest.end - offset),
+                    offset,
+                })
+                .collect_vec(),
+        )
+    }
+
+    fn get(&self, source: i64) -> Option<i64> {
+        let idx = self
+            .pieces
+            .partition_point(|piece| piece.source.end <= source);
+        let piece = self.pieces.get(idx)?;
+        piece
+            .source
+            .contains(&source)
+            .then_some(source + piece.offset)
+    }
+
+    /// Lowest destination of any value in `source`. Within a piece the destinations only grow,
+    /// so only the first value of every overlapped piece matters.
+    fn min_over(&self, source: Range<i64>) -> Option<i64> {
+        let first = self
+            .pieces
+            .partition_point(|piece| piece.source.end <= source.start);
+        self.pieces[first..]
+            .iter()
+            .take_while(|piece| piece.source.start < source.end)
+            .map(|piece| piece.source.start.max(source.start) + piece.offset)
+            .min()
+    }
+
+    /// Every source value which ends up at `dest`.
+    fn inverse(&self, dest: i64) -> impl Iterator<Item = i64> + '_ {
+        let candidates = self
+            .by_dest
+            .partition_point(|piece| piece.dest().start <= dest);
+        self.by_dest[..candidates]
+            .iter()
+            .filter(move |piece| piece.dest().contains(&dest))
+            .map(move |piece| dest - piece.offset)
+    }
+}
+
+fn parse_seeds(seeds: &[u8]) -> Vec<i64> {
+    let (_, seeds) = parse::key_value(seeds).unwrap();
+    parse::numbers(seeds).collect::<Option<_>>().unwrap()
+}
+
+fn parse_seeds_ranges(seeds: &[u8]) -> Vec<Range<i64>> {
+    parse_seeds(seeds)
         .into_iter()
-        .map(|vec| {
-            let parts = vec.collect_vec();
-            let no: i64 = parts[0].parse().unwrap();
-            let range: i64 = parts[1].parse().unwrap();
-            no..(no + range)
-        })
+        .tuples()
+        .map(|(no, range)| no..(no + range))
         .collect_vec()
 }
 
-fn get_maps(map: &str) -> Result<Vec<ConvertMap>, Box<dyn Error>> {
-    map.lines().skip(1).map(ConvertMap::from_string).collect()
+/// One `<source>-to-<dest> map:` block of the almanac.
+#[derive(Debug)]
+struct Category<'a> {
+    source: &'a str,
+    dest: &'a str,
+    maps: Vec<ConvertMap>,
+}
+
+impl<'a> Category<'a> {
+    fn from_bytes(block: &'a [u8]) -> Result<Self, Box<dyn Error>> {
+        let mut lines = block.split(|byte| *byte == b'\n');
+        let header = std::str::from_utf8(lines.next().unwrap_or_default())?;
+        let (source, dest) = header
+            .strip_suffix(" map:")
+            .and_then(|names| names.split_once("-to-"))
+            .ok_or("Wrong Input! Expected: <source>-to-<dest> map:")?;
+        let maps = lines
+            .map(ConvertMap::from_bytes)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { source, dest, maps })
+    }
+}
+
+struct Almanac<'a> {
+    seeds: &'a [u8],
+    categories: Vec<Category<'a>>,
 }
 
-fn seed_location(mut source: i64, categories: &[Vec<ConvertMap>]) -> i64 {
-    for category in categories {
-        let valid = category.iter().find(|map| map.convert(source).is_some());
-        if let Some(map) = valid {
-            source = map.convert(source).unwrap();
+impl<'a> Almanac<'a> {
+    fn from_bytes(input: &'a [u8]) -> Result<Self, Box<dyn Error>> {
+        let mut blocks = parse::blocks(input);
+        let seeds = blocks.next().ok_or("Wrong Input! Missing seeds")?;
+        let categories = blocks.map(Category::from_bytes).collect::<Result<_, _>>()?;
+        Ok(Self { seeds, categories })
+    }
+
+    /// Chains categories by name, in whatever order they appear in the file, into a single
+    /// `source` to `dest` mapping. The shortest chain wins if there's more than one.
+    fn resolve(&self, source: &str, dest: &str) -> Option<Mapping> {
+        let mut reached_by: HashMap<&str, Option<&Category>> = HashMap::from([(source, None)]);
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(name) = queue.pop_front() {
+            if name == dest {
+                let mut chain = Vec::new();
+                let mut curr = name;
+                while let Some(category) = reached_by[curr] {
+                    chain.push(category);
+                    curr = category.source;
+                }
+                chain.reverse();
+                return Some(Mapping::compose(&chain));
+            }
+
+            for category in self.categories.iter().filter(|cat| cat.source == name) {
+                if !reached_by.contains_key(category.dest) {
+                    reached_by.insert(category.dest, Some(category));
+                    queue.push_back(category.dest);
+                }
+            }
         }
+        None
     }
-    source
 }
 
-fn part1(seeds: &[i64], categories: &[Vec<ConvertMap>]) -> i64 {
+fn part1(seeds: &[i64], seed_location: &Mapping) -> i64 {
     seeds
         .iter()
-        .map(|seed| seed_location(*seed, categories))
+        .filter_map(|seed| seed_location.get(*seed))
         .min()
         .unwrap_or(0)
 }
 
-fn part2(seeds_range: &[Range<i64>], categories: &[Vec<ConvertMap>]) -> i64 {
-    let mut location = i64::MAX;
-    for range in seeds_range.iter() {
-        location = std::cmp::min(
-            range
-                .clone()
-                .map(|seed| seed_location(seed, categories))
-                .min()
-                .unwrap_or(i64::MAX),
-            location,
-        );
-    }
-    location
+fn part2(seeds_range: &[Range<i64>], seed_location: &Mapping) -> i64 {
+    seeds_range
+        .iter()
+        .filter_map(|range| seed_location.min_over(range.clone()))
+        .min()
+        .unwrap_or(i64::MAX)
+}
+
+/// Which seeds end up at each of the given locations.
+pub fn seeds_for(input: &[u8], args: &[String]) -> String {
+    let almanac = Almanac::from_bytes(input).unwrap();
+    let seed_location = almanac.resolve("seed", "location").unwrap();
+    args.iter()
+        .map(|location| {
+            let location = parse::number(location.as_bytes()).expect("Locations are numbers");
+            let seeds = seed_location.inverse(location).join(", ");
+            format!("location {location}: seeds {seeds}")
+        })
+        .join("\n")
+}
+
+/// Converts the given values between any two categories, e.g. `soil humidity 81 14`.
+pub fn convert(input: &[u8], args: &[String]) -> String {
+    let usage = "Usage: --convert <source> <dest> [values]";
+    let [source, dest, values @ ..] = args else {
+        panic!("{usage}");
+    };
+    let almanac = Almanac::from_bytes(input).unwrap();
+    let Some(mapping) = almanac.resolve(source, dest) else {
+        return format!("no chain of maps leads from {source} to {dest}");
+    };
+
+    values
+        .iter()
+        .map(|value| {
+            let value = parse::number(value.as_bytes()).expect(usage);
+            match mapping.get(value) {
+                Some(converted) => format!("{source} {value}: {dest} {converted}"),
+                None => format!("{source} {value}: out of range"),
+            }
+        })
+        .join("\n")
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day05");
-    let categories = input
-        .split("\n\n")
-        .skip(1)
-        .map(get_maps)
-        .map(|map| map.unwrap())
-        .collect_vec();
+pub fn main(input: &[u8]) -> String {
+    let almanac = Almanac::from_bytes(input).unwrap();
+    let seed_location = almanac.resolve("seed", "location").unwrap();
 
-    let seeds = parse_seeds(input.lines().next().unwrap());
-    let part1 = part1(&seeds, &categories);
-    println!("part 1: {part1}");
+    let seeds = parse_seeds(almanac.seeds);
+    let part1 = part1(&seeds, &seed_location);
 
-    let seeds_ranges = parse_seeds_ranges(input.lines().next().unwrap());
-    let part2 = part2(&seeds_ranges, &categories);
-    println!("part 2: {part2}");
+    let seeds_ranges = parse_seeds_ranges(almanac.seeds);
+    let part2 = part2(&seeds_ranges, &seed_location);
+    format!("part 1: {part1}\npart 2: {part2}")
 }
 
 #[cfg(test)]
@@ -116,7 +345,7 @@ mod test {
 
     #[test]
     fn test_convert_from_string() {
-        let convert = ConvertMap::from_string("50 98 2").unwrap();
+        let convert = ConvertMap::from_bytes(b"50 98 2").unwrap();
         let valid = ConvertMap {
             dest_start: 50,
             source_start: 98,
@@ -147,4 +376,69 @@ mod test {
         assert_eq!(convert_map.convert(53), Some(55));
         assert_eq!(convert_map.convert(49), None);
     }
+
+    #[test]
+    fn test_compose() {
+        // categories out of chain order on purpose
+        let almanac = Almanac::from_bytes(
+            b"seeds: 79 14 55 13\n\n\
+            soil-to-fertilizer map:\n0 15 37\n37 52 2\n39 0 15\n\n\
+            seed-to-soil map:\n50 98 2\n52 50 48",
+        )
+        .unwrap();
+        assert!(almanac.resolve("fertilizer", "seed").is_none());
+        let mapping = almanac.resolve("seed", "fertilizer").unwrap();
+        // seed -> soil -> fertilizer, one category at a time
+        for (seed, fertilizer) in [(79, 81), (14, 53), (55, 57), (13, 52), (98, 35), (200, 200)] {
+            assert_eq!(mapping.get(seed), Some(fertilizer));
+            assert!(mapping.inverse(fertilizer).contains(&seed));
+        }
+        assert_eq!(mapping.get(-1), None);
+        assert_eq!(mapping.min_over(48..52), Some(33));
+    }
+
+    #[test]
+    fn maps_inside_domain() {
+        assert!(ConvertMap::from_bytes(b"5 0 9223372036854775807").is_err());
+        assert!(ConvertMap::from_bytes(b"0 5 9223372036854775807").is_err());
+        assert!(ConvertMap::from_bytes(b"0 -5 3").is_err());
+        assert!(ConvertMap::from_bytes(b"0 5 -3").is_err());
+
+        let edge = ConvertMap::from_bytes(b"0 5 9223372036854775802").unwrap();
+        assert_eq!(
+            edge.convert_range(0..i64::MAX),
+            (Some(0..9223372036854775802), [Some(0..5), None])
+        );
+        let mapping = Mapping::from_pieces(vec![Piece {
+            source: DOMAIN,
+            offset: 0,
+        }])
+        .then(&[edge]);
+        assert_eq!(mapping.get(i64::MAX - 1), Some(i64::MAX - 6));
+    }
+
+    #[test]
+    fn test_convert_range() {
+        let convert_map = ConvertMap {
+            dest_start: 52,
+            source_start: 50,
+            range: 48,
+        };
+        assert_eq!(
+            convert_map.convert_range(40..60),
+            (Some(52..62), [Some(40..50), None])
+        );
+        assert_eq!(
+            convert_map.convert_range(90..100),
+            (Some(92..100), [None, Some(98..100)])
+        );
+        assert_eq!(
+            convert_map.convert_range(0..10),
+            (None, [Some(0..10), None])
+        );
+        assert_eq!(
+            convert_map.convert_range(40..110),
+            (Some(52..100), [Some(40..50), Some(98..110)])
+        );
+    }
 }
@@ -1,68 +1,118 @@
+use crate::parse;
+
 #[derive(Debug)]
 struct Race {
-    time: usize,
-    min_distance: usize,
+    time: u64,
+    min_distance: u64,
 }
 
 impl Race {
-    fn get_win_number(&self) -> usize {
-        let delta: f64 = ((self.time * self.time - 4 * self.min_distance) as f64).sqrt();
-        let min_win = ((self.time as f64 - delta) / 2f64).ceil() as usize;
-        let max_win = ((self.time as f64 + delta) / 2f64).floor() as usize;
+    fn distance(&self, hold: u128) -> u128 {
+        hold * (self.time as u128 - hold)
+    }
 
-        max_win - min_win + 1
+    fn beats_record(&self, hold: u128) -> bool {
+        self.distance(hold) > self.min_distance as u128
+    }
+
+    // Winning hold times are the integers strictly between the roots of
+    // hold * (time - hold) = min_distance. The integer square root only gets close to the lower
+    // root, so step it onto the first winning hold. The distances are symmetric around time / 2,
+    // which gives the upper bound for free.
+    fn get_win_number(&self) -> u64 {
+        let time = self.time as u128;
+        let Some(delta) = (time * time).checked_sub(4 * self.min_distance as u128) else {
+            return 0;
+        };
+
+        let mut min_win = (time - delta.isqrt()) / 2;
+        while min_win <= time / 2 && !self.beats_record(min_win) {
+            min_win += 1;
+        }
+        while min_win > 0 && self.beats_record(min_win - 1) {
+            min_win -= 1;
+        }
+
+        // nothing up to the peak wins, so nothing does, and min_win may be past the time already
+        if min_win > time / 2 {
+            return 0;
+        }
+        let max_win = time - min_win;
+        (max_win - min_win + 1) as u64
     }
 }
 
-fn parse_input_part1(input: &str) -> Vec<Race> {
-    input
-        .lines()
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .skip(1)
-        .zip(input.lines().nth(1).unwrap().split_whitespace().skip(1))
-        .map(|(time, distance)| Race {
-            time: time.parse().unwrap(),
-            min_distance: distance.parse().unwrap(),
-        })
-        .collect()
+// `None` unless both lines are labeled lists of numbers of the same length
+fn parse_input_part1(input: &str) -> Option<Vec<Race>> {
+    let mut lines = input.lines().map(|line| {
+        let (_, values) = parse::key_value(line.as_bytes())?;
+        parse::numbers(values).collect::<Option<Vec<u64>>>()
+    });
+    let times = lines.next()??;
+    let distances = lines.next()??;
+    if times.len() != distances.len() {
+        return None;
+    }
+
+    let races = times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, min_distance)| Race { time, min_distance });
+    Some(races.collect())
 }
 
 fn parse_input_part2(input: &str) -> Race {
-    let time = input
+    let mut values = input
         .lines()
-        .next()
-        .unwrap()
-        .chars()
-        .filter(|chr| chr.is_ascii_digit())
-        .collect::<String>()
-        .parse::<usize>()
-        .unwrap();
+        .map(|line| parse::key_value(line.as_bytes()).unwrap().1)
+        .map(|value| parse::digits(value).unwrap());
 
-    let min_distance = input
-        .lines()
-        .nth(1)
-        .unwrap()
-        .chars()
-        .filter(|chr| chr.is_ascii_digit())
-        .collect::<String>()
-        .parse::<usize>()
-        .unwrap();
-
-    Race { time, min_distance }
+    Race {
+        time: values.next().unwrap(),
+        min_distance: values.next().unwrap(),
+    }
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day06");
-    let parsed_part1 = parse_input_part1(input);
+pub fn main(input: &[u8]) -> String {
+    let input = std::str::from_utf8(input).unwrap();
+    let parsed_part1 = parse_input_part1(input).expect("Malformed race times or distances");
     let part1 = parsed_part1
         .iter()
         .map(|race| race.get_win_number())
-        .product::<usize>();
-    println!("part 1: {part1}");
+        .product::<u64>();
 
     let parsed_part2 = parse_input_part2(input);
     let part2 = parsed_part2.get_win_number();
-    println!("part 2: {part2}");
+    format!("part 1: {part1}\npart 2: {part2}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exact_win_numbers() {
+        let race = |time, min_distance| Race { time, min_distance }.get_win_number();
+        assert_eq!(race(7, 9), 4);
+        assert_eq!(race(15, 40), 8);
+        // roots 10 and 20 are exact, so ties must not count
+        assert_eq!(race(30, 200), 9);
+        assert_eq!(race(71530, 940200), 71503);
+        // the best possible hold only ties the record
+        assert_eq!(race(10, 25), 0);
+        assert_eq!(race(10, 30), 0);
+        assert_eq!(race(0, 0), 0);
+        assert_eq!(race(1, 0), 0);
+        assert_eq!(race(2, 0), 1);
+        assert_eq!(race(u64::MAX, u64::MAX), u64::MAX - 3);
+    }
+
+    #[test]
+    fn malformed_races() {
+        let races = parse_input_part1("Time:      7  15   30\nDistance:  9  40  200").unwrap();
+        assert_eq!(races.len(), 3);
+        assert!(parse_input_part1("Time:      7  1x5   30\nDistance:  9  40  200").is_none());
+        assert!(parse_input_part1("Time:      7  15\nDistance:  9  40  200").is_none());
+        assert!(parse_input_part1("Time:      7  15   30").is_none());
+    }
 }
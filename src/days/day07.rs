@@ -1,19 +1,27 @@
-use std::collections::HashMap;
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt,
+};
 
 use itertools::Itertools;
 
+use crate::parse;
+
+// weakest first, so the discriminant doubles as the rank's strength
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 enum Rank {
-    FiveOfKind,
-    FourOfKind,
-    FullHouse,
-    ThreeOfKind,
-    TwoPair,
-    OnePair,
     HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfKind,
+    FullHouse,
+    FourOfKind,
+    FiveOfKind,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+const HAND_SIZE: usize = 5;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 enum Card {
     Ace,
     King,
@@ -28,7 +36,28 @@ enum Card {
     Four,
     Three,
     Two,
-    //Jack,
+}
+
+const CARDS: [Card; 13] = [
+    Card::Ace,
+    Card::King,
+    Card::Queen,
+    Card::Jack,
+    Card::Tower,
+    Card::Nine,
+    Card::Eight,
+    Card::Seven,
+    Card::Six,
+    Card::Five,
+    Card::Four,
+    Card::Three,
+    Card::Two,
+];
+
+impl From<Card> for char {
+    fn from(card: Card) -> Self {
+        b"AKQJT98765432"[card as usize] as char
+    }
 }
 
 impl TryFrom<char> for Card {
@@ -54,99 +83,281 @@ impl TryFrom<char> for Card {
     }
 }
 
-#[derive(Debug)]
-struct Draw {
-    rank: Rank,
-    cards: Vec<Card>,
-    bid: usize,
+/// How cards compare against each other and which card, if any, stands in for any other.
+#[derive(Debug, Clone)]
+struct Ruleset {
+    // strength of every card indexed by its discriminant, 0 being the weakest
+    values: [u8; 13],
+    wildcard: Option<Card>,
 }
 
-impl Draw {
-    #[allow(dead_code)]
-    fn get_rank_frequency_part1(frequency: HashMap<char, u8>) -> Rank {
-        let mut max_val = 1;
-        for val in frequency.into_values() {
-            match (val, max_val) {
-                (2, 2) => return Rank::TwoPair,
-                (3, 2) | (2, 3) => return Rank::FullHouse,
-                _ => {}
-            };
+impl Ruleset {
+    fn new(order: &str, wildcard: Option<char>) -> Result<Self, &'static str> {
+        let order: Vec<Card> = order.chars().map(Card::try_from).try_collect()?;
+        if !order.iter().all_unique() {
+            return Err("Every card can be in the order only once");
+        }
+        if order.len() != 13 {
+            return Err("The order has to contain all 13 cards");
+        }
+        let mut values = [0; 13];
+        for (strength, card) in order.iter().rev().enumerate() {
+            values[*card as usize] = strength as u8;
+        }
+        let wildcard = wildcard.map(Card::try_from).transpose()?;
 
-            max_val = std::cmp::max(max_val, val);
+        Ok(Self { values, wildcard })
+    }
+
+    fn standard() -> Self {
+        Self::new("AKQJT98765432", None).unwrap()
+    }
+
+    fn jokers() -> Self {
+        Self::new("AKQT98765432J", Some('J')).unwrap()
+    }
+
+    fn card_value(&self, card: Card) -> u8 {
+        self.values[card as usize]
+    }
+
+    fn rank(&self, cards: &[Card; HAND_SIZE]) -> Rank {
+        let mut counts = [0u8; 13];
+        for card in cards {
+            counts[*card as usize] += 1;
+        }
+        let wildcards = self
+            .wildcard
+            .map_or(0, |wildcard| std::mem::take(&mut counts[wildcard as usize]));
+
+        let (mut most, mut second) = (0, 0);
+        for count in counts {
+            if count > most {
+                (most, second) = (count, most);
+            } else if count > second {
+                second = count;
+            }
         }
-        match max_val {
-            1 => Rank::HighCard,
-            2 => Rank::OnePair,
-            3 => Rank::ThreeOfKind,
-            4 => Rank::FourOfKind,
-            5 => Rank::FiveOfKind,
-            _ => unreachable!(),
+
+        // wildcards always do best joining the biggest group
+        match (most + wildcards, second) {
+            (5, _) => Rank::FiveOfKind,
+            (4, _) => Rank::FourOfKind,
+            (3, 2) => Rank::FullHouse,
+            (3, _) => Rank::ThreeOfKind,
+            (2, 2) => Rank::TwoPair,
+            (2, _) => Rank::OnePair,
+            _ => Rank::HighCard,
+        }
+    }
+
+    /// Everything that goes into the hand's strength, spelled out.
+    fn evaluate(&self, cards: &[Card; HAND_SIZE]) -> Evaluation {
+        let wildcards = cards
+            .iter()
+            .filter(|card| Some(**card) == self.wildcard)
+            .count();
+        let counts = cards
+            .iter()
+            .filter(|card| Some(**card) != self.wildcard)
+            .counts()
+            .into_iter()
+            .map(|(card, count)| (*card, count))
+            .sorted_by_key(|(card, count)| (Reverse(*count), Reverse(self.card_value(*card))))
+            .collect_vec();
+        // only the size of the biggest group matters, so a hand of nothing but wildcards may as
+        // well become the strongest card
+        let substitute = (wildcards > 0).then(|| {
+            counts.first().map_or_else(
+                || {
+                    let strongest = CARDS.iter().max_by_key(|card| self.card_value(**card));
+                    *strongest.unwrap()
+                },
+                |(card, _)| *card,
+            )
+        });
+
+        Evaluation {
+            cards: *cards,
+            counts,
+            wildcards,
+            substitute,
+            rank: self.rank(cards),
+            tie_break: cards.map(|card| self.card_value(card)),
         }
     }
 
-    fn get_rank_frequency_part2(mut frequency: HashMap<char, u8>) -> Rank {
-        let mut max_val = 0;
-        let jokers = frequency.remove(&'J').unwrap_or(0);
-        for val in frequency.into_values() {
-            match (val, max_val) {
-                (2, 2) => {
-                    if jokers == 0 {
-                        return Rank::TwoPair;
-                    } else {
-                        return Rank::FullHouse;
-                    }
-                }
-                (3, 2) | (2, 3) => return Rank::FullHouse,
-                _ => {}
+    // the rank followed by every card's value, 4 bits each, so hands compare as plain integers
+    fn hand_strength(&self, cards: &[Card; HAND_SIZE]) -> u32 {
+        cards.iter().fold(self.rank(cards) as u32, |acc, card| {
+            (acc << 4) | self.card_value(*card) as u32
+        })
+    }
+}
+
+struct Evaluation {
+    cards: [Card; HAND_SIZE],
+    // every card other than the wildcard with how often it's in the hand, biggest groups first
+    counts: Vec<(Card, usize)>,
+    wildcards: usize,
+    // the card the wildcards pretend to be
+    substitute: Option<Card>,
+    rank: Rank,
+    // value of every card in hand order, compared only when the ranks are equal
+    tie_break: [u8; HAND_SIZE],
+}
+
+impl Evaluation {
+    fn hand(&self) -> String {
+        self.cards.iter().map(|card| char::from(*card)).collect()
+    }
+
+    /// Which of the two hands wins and the first thing that sets them apart.
+    fn compare(&self, other: &Self) -> String {
+        let (hand, other_hand) = (self.hand(), other.hand());
+        if self.rank != other.rank {
+            let (winner, loser) = match self.rank.cmp(&other.rank) {
+                Ordering::Greater => ((hand, self.rank), (other_hand, other.rank)),
+                _ => ((other_hand, other.rank), (hand, self.rank)),
             };
+            return format!(
+                "{} wins: {:?} beats {:?} of {}",
+                winner.0, winner.1, loser.1, loser.0
+            );
+        }
+
+        let differing = (0..HAND_SIZE).find(|idx| self.tie_break[*idx] != other.tie_break[*idx]);
+        let Some(idx) = differing else {
+            return format!(
+                "{hand} and {other_hand} tie: both {:?} with equal cards",
+                self.rank
+            );
+        };
+        let (winner, card, other_card) = if self.tie_break[idx] > other.tie_break[idx] {
+            (hand, self.cards[idx], other.cards[idx])
+        } else {
+            (other_hand, other.cards[idx], self.cards[idx])
+        };
+        format!(
+            "{winner} wins: both {:?}, card {} {} beats {}",
+            self.rank,
+            idx + 1,
+            char::from(card),
+            char::from(other_card)
+        )
+    }
+}
 
-            max_val = std::cmp::max(max_val, val);
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut counts = self
+            .counts
+            .iter()
+            .map(|(card, count)| format!("{}x{count}", char::from(*card)))
+            .join(" ");
+        if counts.is_empty() {
+            counts.push('-');
         }
-        max_val += jokers;
-        match max_val {
-            1 => Rank::HighCard,
-            2 => Rank::OnePair,
-            3 => Rank::ThreeOfKind,
-            4 => Rank::FourOfKind,
-            5 => Rank::FiveOfKind,
-            _ => unreachable!(),
+        write!(f, "{}: counts {counts}", self.hand())?;
+        match self.substitute {
+            Some(card) => write!(f, ", {} wildcards as {}", self.wildcards, char::from(card))?,
+            None => write!(f, ", no wildcards")?,
         }
+        write!(f, " => {:?}, tie-break {:?}", self.rank, self.tie_break)
     }
+}
 
+#[derive(Debug)]
+struct Draw {
+    cards: [Card; HAND_SIZE],
+    bid: usize,
+}
+
+impl Draw {
     fn parse_draw(draw: &str) -> Self {
         let (cards_txt, bid) = draw.split_once(' ').unwrap();
-        let mut cards: Vec<Card> = Vec::with_capacity(cards_txt.len());
-        let mut frequency: HashMap<char, u8> = HashMap::new();
-
-        for card in cards_txt.chars() {
-            cards.push(card.try_into().unwrap());
-            frequency
-                .entry(card)
-                .and_modify(|val| *val += 1)
-                .or_insert(1);
+        assert_eq!(cards_txt.len(), HAND_SIZE, "A hand has {HAND_SIZE} cards");
+        let mut cards = [Card::Two; HAND_SIZE];
+        for (card, chr) in cards.iter_mut().zip(cards_txt.chars()) {
+            *card = chr.try_into().unwrap();
         }
 
-        let rank = Self::get_rank_frequency_part2(frequency);
-
         Self {
-            rank,
             cards,
-            bid: bid.parse().unwrap(),
+            bid: parse::number(bid.as_bytes()).unwrap(),
         }
     }
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day07");
-    let mut parsed = input.lines().map(Draw::parse_draw).collect_vec();
-    parsed.sort_by_key(|draw| (draw.rank, draw.cards.clone()));
-    let part = parsed
+fn total_winnings(draws: &mut [Draw], ruleset: &Ruleset) -> usize {
+    draws.sort_unstable_by_key(|draw| ruleset.hand_strength(&draw.cards));
+    draws
         .iter()
-        .rev()
         .enumerate()
         .map(|(val, draw)| (val + 1) * draw.bid)
-        .sum::<usize>();
-    println!("part: {part}");
+        .sum::<usize>()
+}
+
+fn parse_draws(input: &[u8]) -> Vec<Draw> {
+    let input = std::str::from_utf8(input).unwrap();
+    input.lines().map(Draw::parse_draw).collect_vec()
+}
+
+/// Total winnings under a custom ruleset given as the card order, strongest first, and an
+/// optional wildcard, e.g. `AKQT98765432J J`.
+pub fn ruleset(input: &[u8], args: &[String]) -> String {
+    let usage = "Usage: --ruleset <order> [wildcard]";
+    let ruleset = match args {
+        [order] => Ruleset::new(order, None),
+        [order, wildcard] if wildcard.chars().count() == 1 => {
+            Ruleset::new(order, wildcard.chars().next())
+        }
+        _ => panic!("{usage}"),
+    }
+    .unwrap_or_else(|err| panic!("{err}\n{usage}"));
+
+    format!("{}", total_winnings(&mut parse_draws(input), &ruleset))
+}
+
+/// How both parts' rulesets rate a hand, or which of two hands wins under each and why.
+pub fn explain(_input: &[u8], args: &[String]) -> String {
+    let hands = match args {
+        [_] | [_, _] => args
+            .iter()
+            .map(|hand| Draw::parse_draw(&format!("{hand} 0")).cards)
+            .collect_vec(),
+        _ => panic!("Usage: --explain <hand> [other hand]"),
+    };
+
+    [
+        ("part 1", Ruleset::standard()),
+        ("part 2", Ruleset::jokers()),
+    ]
+    .iter()
+    .map(|(name, ruleset)| {
+        let evaluations = hands
+            .iter()
+            .map(|hand| ruleset.evaluate(hand))
+            .collect_vec();
+        let mut lines = vec![format!("{name}:")];
+        lines.extend(
+            evaluations
+                .iter()
+                .map(|evaluation| format!("  {evaluation}")),
+        );
+        if let [first, second] = &evaluations[..] {
+            lines.push(format!("  {}", first.compare(second)));
+        }
+        lines.join("\n")
+    })
+    .join("\n")
+}
+
+pub fn main(input: &[u8]) -> String {
+    let mut draws = parse_draws(input);
+    let part1 = total_winnings(&mut draws, &Ruleset::standard());
+    let part2 = total_winnings(&mut draws, &Ruleset::jokers());
+    format!("part 1: {part1}\npart 2: {part2}")
 }
 
 #[cfg(test)]
@@ -155,11 +366,90 @@ mod test {
 
     #[test]
     fn valid_card_parse() {
-        assert_eq!(Rank::FiveOfKind, Draw::parse_draw("AJJAA 1").rank);
-        assert_eq!(Rank::HighCard, Draw::parse_draw("23456 1").rank);
-        assert_eq!(Rank::FourOfKind, Draw::parse_draw("2JJAA 1").rank);
-        assert_eq!(Rank::FiveOfKind, Draw::parse_draw("AAAAA 1").rank);
-        assert_eq!(Rank::FiveOfKind, Draw::parse_draw("QQJQQ 1").rank);
-        assert_eq!(Rank::ThreeOfKind, Draw::parse_draw("A23AA 1").rank);
+        let jokers = Ruleset::jokers();
+        assert_eq!(
+            Rank::FiveOfKind,
+            jokers.rank(&Draw::parse_draw("AJJAA 1").cards)
+        );
+        assert_eq!(
+            Rank::HighCard,
+            jokers.rank(&Draw::parse_draw("23456 1").cards)
+        );
+        assert_eq!(
+            Rank::FourOfKind,
+            jokers.rank(&Draw::parse_draw("2JJAA 1").cards)
+        );
+        assert_eq!(
+            Rank::FiveOfKind,
+            jokers.rank(&Draw::parse_draw("AAAAA 1").cards)
+        );
+        assert_eq!(
+            Rank::FiveOfKind,
+            jokers.rank(&Draw::parse_draw("QQJQQ 1").cards)
+        );
+        assert_eq!(
+            Rank::ThreeOfKind,
+            jokers.rank(&Draw::parse_draw("A23AA 1").cards)
+        );
+    }
+
+    #[test]
+    fn standard_card_parse() {
+        let standard = Ruleset::standard();
+        assert_eq!(
+            Rank::FullHouse,
+            standard.rank(&Draw::parse_draw("AJJAA 1").cards)
+        );
+        assert_eq!(
+            Rank::TwoPair,
+            standard.rank(&Draw::parse_draw("2JJAA 1").cards)
+        );
+        assert_eq!(
+            Rank::OnePair,
+            standard.rank(&Draw::parse_draw("QQJ23 1").cards)
+        );
+        assert!(Ruleset::new("AKQJT9876543", None).is_err());
+        assert!(Ruleset::new("AKQJT98765433", None).is_err());
+    }
+
+    #[test]
+    fn hand_strength_order() {
+        let strength = |hand: &str, ruleset: &Ruleset| {
+            ruleset.hand_strength(&Draw::parse_draw(&format!("{hand} 1")).cards)
+        };
+        let (standard, jokers) = (Ruleset::standard(), Ruleset::jokers());
+        assert!(strength("33332", &standard) > strength("2AAAA", &standard));
+        assert!(strength("JKKK2", &standard) < strength("QQQQ2", &standard));
+        assert!(strength("JKKK2", &jokers) < strength("QQQQ2", &jokers));
+        assert!(strength("KTJJT", &jokers) > strength("QQQJA", &jokers));
+        assert!(strength("2345J", &jokers) > strength("AKQT9", &jokers));
+    }
+
+    #[test]
+    fn explain_hands() {
+        let jokers = Ruleset::jokers();
+        let evaluation = jokers.evaluate(&Draw::parse_draw("KTJJT 1").cards);
+        assert_eq!(evaluation.rank, Rank::FourOfKind);
+        assert_eq!(evaluation.substitute, Some(Card::Tower));
+        assert_eq!(
+            evaluation.to_string(),
+            "KTJJT: counts Tx2 Kx1, 2 wildcards as T => FourOfKind, tie-break [11, 9, 0, 0, 9]"
+        );
+        let all_jokers = jokers.evaluate(&Draw::parse_draw("JJJJJ 1").cards);
+        assert_eq!(all_jokers.substitute, Some(Card::Ace));
+        assert_eq!(all_jokers.rank, Rank::FiveOfKind);
+
+        let other = jokers.evaluate(&Draw::parse_draw("QQQJA 1").cards);
+        assert_eq!(
+            evaluation.compare(&other),
+            "KTJJT wins: both FourOfKind, card 1 K beats Q"
+        );
+        let standard = Ruleset::standard();
+        assert_eq!(
+            standard
+                .evaluate(&Draw::parse_draw("KTJJT 1").cards)
+                .compare(&standard.evaluate(&Draw::parse_draw("QQQJA 1").cards)),
+            "QQQJA wins: ThreeOfKind beats TwoPair of KTJJT"
+        );
     }
 }
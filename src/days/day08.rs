@@ -1,9 +1,12 @@
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     hash::{Hash, Hasher},
 };
 
 use itertools::Itertools;
+use num::Integer;
+
+use crate::cycle::{self, Cycle};
 
 #[derive(Hash, Debug)]
 struct Entry {
@@ -88,25 +91,122 @@ fn part1(directions: &[Directions], entries: &HashMap<u64, Entry>) -> usize {
     unreachable!()
 }
 
-fn part2(directions: &[Directions], entries: &HashMap<u64, Entry>, mut curr_point: u64) -> usize {
-    for (step, direction) in directions.iter().cycle().enumerate() {
-        curr_point = {
-            let entry = entries.get(&curr_point).unwrap();
-            if entry.ghost_status == GhostStatus::EndNode {
-                return step;
-            }
+// a ghost's whereabouts: the node it stands on and the instruction it follows next
+type State = (u64, usize);
 
-            match direction {
-                Directions::Left => entry.left,
-                Directions::Right => entry.right,
-            }
+fn next_state(directions: &[Directions], entries: &HashMap<u64, Entry>, state: &State) -> State {
+    let (node, idx) = *state;
+    let entry = entries.get(&node).unwrap();
+    let next = match directions[idx] {
+        Directions::Left => entry.left,
+        Directions::Right => entry.right,
+    };
+    (next, (idx + 1) % directions.len())
+}
+
+/// The path of a single ghost: after `cycle.start` steps it keeps walking the same loop of
+/// states, so knowing where it hits an end node up to there tells where it does forever after.
+struct Ghost {
+    cycle: Cycle,
+    // steps after which the ghost stands on an end node, all below `cycle.start + cycle.period`
+    end_hits: Vec<usize>,
+}
+
+impl Ghost {
+    fn walk(directions: &[Directions], entries: &HashMap<u64, Entry>, start: u64) -> Self {
+        let step = |state: &State| next_state(directions, entries, state);
+        let cycle = cycle::detect(&(start, 0), step);
+        let end_hits = std::iter::successors(Some((start, 0)), |state| Some(step(state)))
+            .take(cycle.start + cycle.period)
+            .positions(|(node, _)| entries[&node].ghost_status == GhostStatus::EndNode)
+            .collect_vec();
+
+        Self { cycle, end_hits }
+    }
+
+    fn is_hit(&self, steps: usize) -> bool {
+        let Cycle { start, period } = self.cycle;
+        let steps = if steps < start + period {
+            steps
+        } else {
+            start + (steps - start) % period
         };
+        self.end_hits.binary_search(&steps).is_ok()
+    }
+
+    // residues modulo the period of all the end hits which keep coming back
+    fn looping_hits(&self) -> impl Iterator<Item = usize> + '_ {
+        self.end_hits
+            .iter()
+            .filter(|hit| **hit >= self.cycle.start)
+            .map(|hit| hit % self.cycle.period)
+    }
+
+    // the only end hit is exactly one period in and, being inside the loop already, repeats
+    // every period, which is what makes the LCM of the periods the answer
+    fn is_clean(&self) -> bool {
+        self.end_hits == [self.cycle.period] && self.cycle.start <= self.cycle.period
     }
-    unreachable!();
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day08");
+// merges x = a (mod m) with x = b (mod n) into one congruence, if they agree at all
+fn crt((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let egcd = m.extended_gcd(&n);
+    if (b - a) % egcd.gcd != 0 {
+        return None;
+    }
+    let lcm = m / egcd.gcd * n;
+    let x = a + (b - a) / egcd.gcd * egcd.x % (n / egcd.gcd) * m;
+    Some((x.rem_euclid(lcm), lcm))
+}
+
+/// The fewest steps after which all ghosts stand on end nodes at once, if that ever happens.
+fn steps_until_all_end(ghosts: &[Ghost]) -> Option<usize> {
+    if ghosts.iter().all(Ghost::is_clean) {
+        return Some(
+            ghosts
+                .iter()
+                .map(|ghost| ghost.cycle.period)
+                .fold(1, num::integer::lcm),
+        );
+    }
+
+    // before the last ghost settles into its loop the hits don't follow any pattern
+    let settled = ghosts
+        .iter()
+        .map(|ghost| ghost.cycle.start)
+        .max()
+        .unwrap_or(0);
+    if let Some(steps) = (0..settled).find(|steps| ghosts.iter().all(|ghost| ghost.is_hit(*steps)))
+    {
+        return Some(steps);
+    }
+
+    // afterwards every ghost wants one of its residues, try every combination of them
+    let mut congruences = vec![(0i128, 1i128)];
+    for ghost in ghosts {
+        let period = ghost.cycle.period as i128;
+        congruences = congruences
+            .iter()
+            .cartesian_product(ghost.looping_hits().collect_vec())
+            .filter_map(|(congruence, hit)| crt(*congruence, (hit as i128, period)))
+            .unique()
+            .collect_vec();
+    }
+
+    congruences
+        .into_iter()
+        .map(|(residue, modulus)| {
+            let settled = settled as i128;
+            let behind = (settled - residue).max(0);
+            residue + (behind + modulus - 1) / modulus * modulus
+        })
+        .min()
+        .map(|steps| steps as usize)
+}
+
+fn parse_network(input: &[u8]) -> (Vec<Directions>, HashMap<u64, Entry>) {
+    let input = std::str::from_utf8(input).unwrap();
     let directions = input
         .lines()
         .next()
@@ -114,19 +214,252 @@ pub fn main() {
         .chars()
         .map(|chr| Directions::try_from(chr).unwrap())
         .collect_vec();
-    let entries: HashMap<u64, Entry> = input.lines().skip(2).map(parse_entry).collect();
-    let p1 = part1(&directions, &entries);
-    println!("part 1: {p1}");
+    let entries = input.lines().skip(2).map(parse_entry).collect();
+    (directions, entries)
+}
 
-    let ghost_start_nodes = entries
+fn ghosts(directions: &[Directions], entries: &HashMap<u64, Entry>) -> Vec<Ghost> {
+    entries
         .iter()
         .filter(|(_, val)| val.ghost_status == GhostStatus::StartNode)
-        .map(|(key, _)| *key)
+        .map(|(key, _)| Ghost::walk(directions, entries, *key))
+        .collect_vec()
+}
+
+// entries only know each other by the hash of their name, this gets the names back for reports
+fn node_names(input: &[u8]) -> HashMap<u64, String> {
+    let input = std::str::from_utf8(input).unwrap();
+    input
+        .lines()
+        .skip(2)
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(name, _)| (calculate_hash(name), name.to_owned()))
+        .collect()
+}
+
+/// Strongly connected components of the network, Kosaraju style.
+fn components(entries: &HashMap<u64, Entry>) -> Vec<Vec<u64>> {
+    let nodes = entries.keys().copied().sorted().collect_vec();
+    let successors = |node: u64| {
+        let entry = &entries[&node];
+        [entry.left, entry.right]
+    };
+
+    // nodes in the order their depth first search finishes
+    let mut visited = HashSet::new();
+    let mut finished = Vec::with_capacity(nodes.len());
+    for root in &nodes {
+        if !visited.insert(*root) {
+            continue;
+        }
+        let mut stack = vec![(*root, 0)];
+        while let Some(top) = stack.last_mut() {
+            let (node, child) = *top;
+            if child == 2 {
+                finished.push(node);
+                stack.pop();
+                continue;
+            }
+            top.1 += 1;
+            let next = successors(node)[child];
+            if visited.insert(next) {
+                stack.push((next, 0));
+            }
+        }
+    }
+
+    let mut predecessors: HashMap<u64, Vec<u64>> = HashMap::new();
+    for node in &nodes {
+        for next in successors(*node) {
+            predecessors.entry(next).or_default().push(*node);
+        }
+    }
+
+    // going backwards from the last finished node only ever reaches its own component
+    let mut assigned = HashSet::new();
+    let mut components = Vec::new();
+    for root in finished.iter().rev() {
+        if !assigned.insert(*root) {
+            continue;
+        }
+        let mut component = vec![*root];
+        let mut stack = vec![*root];
+        while let Some(node) = stack.pop() {
+            for prev in predecessors.get(&node).into_iter().flatten() {
+                if assigned.insert(*prev) {
+                    component.push(*prev);
+                    stack.push(*prev);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+fn reachable(entries: &HashMap<u64, Entry>, start: u64) -> HashSet<u64> {
+    let mut seen = HashSet::from([start]);
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        let entry = &entries[&node];
+        for next in [entry.left, entry.right] {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Reports the loops in the network, which end nodes every start node can get to at all and
+/// how every ghost actually cycles, i.e. everything the LCM answer relies on.
+pub fn analyze(input: &[u8], _args: &[String]) -> String {
+    let (directions, entries) = parse_network(input);
+    let names = node_names(input);
+    let name = |node: &u64| names[node].as_str();
+    let with_status = |status: GhostStatus| {
+        entries
+            .iter()
+            .filter(|(_, entry)| entry.ghost_status == status)
+            .map(|(node, _)| *node)
+            .sorted_by_key(|node| name(node))
+            .collect_vec()
+    };
+    let starts = with_status(GhostStatus::StartNode);
+    let ends = with_status(GhostStatus::EndNode);
+
+    let components = components(&entries);
+    let cyclic = components
+        .iter()
+        .filter(|component| {
+            let entry = &entries[&component[0]];
+            component.len() > 1 || entry.left == component[0] || entry.right == component[0]
+        })
+        .map(|component| {
+            let ends = component
+                .iter()
+                .filter(|node| entries[*node].ghost_status == GhostStatus::EndNode)
+                .map(name)
+                .sorted()
+                .join(", ");
+            (component.len(), ends)
+        })
+        .sorted()
         .collect_vec();
-    let p2 = ghost_start_nodes
+    let mut lines = vec![format!(
+        "components: {}, cyclic: {}",
+        components.len(),
+        cyclic.len()
+    )];
+    lines.extend(cyclic.iter().map(|(size, ends)| {
+        if ends.is_empty() {
+            format!("  size {size}, no end nodes")
+        } else {
+            format!("  size {size}, end nodes {ends}")
+        }
+    }));
+
+    lines.push("reachability:".to_owned());
+    for start in &starts {
+        let seen = reachable(&entries, *start);
+        let reached = ends
+            .iter()
+            .filter(|end| seen.contains(end))
+            .map(name)
+            .join(", ");
+        lines.push(format!("  {} -> {reached}", name(start)));
+    }
+
+    lines.push("ghosts:".to_owned());
+    let ghosts = starts
         .iter()
-        .map(|node| part2(&directions, &entries, *node))
-        .fold(1, num::integer::lcm);
+        .map(|start| Ghost::walk(&directions, &entries, *start))
+        .collect_vec();
+    for (start, ghost) in starts.iter().zip(&ghosts) {
+        lines.push(format!(
+            "  {}: tail {}, period {}, end hits {:?}{}",
+            name(start),
+            ghost.cycle.start,
+            ghost.cycle.period,
+            ghost.end_hits,
+            if ghost.is_clean() { ", clean" } else { "" }
+        ));
+    }
+    lines.push(format!("lcm valid: {}", ghosts.iter().all(Ghost::is_clean)));
+    lines.join("\n")
+}
+
+pub fn main(input: &[u8]) -> String {
+    let (directions, entries) = parse_network(input);
+    let p1 = part1(&directions, &entries);
+    let p2 = steps_until_all_end(&ghosts(&directions, &entries)).expect("The ghosts never meet");
+
+    format!("part 1: {p1}\npart 2: {p2}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the first ghost's only end hit is one period in, but still before its loop, so it never
+    // comes back
+    const TAIL_HIT: &str = "L\n\n1AA = (1BB, 1BB)\n1BB = (1CC, 1CC)\n1CC = (1ZZ, 1ZZ)\n\
+        1ZZ = (1DD, 1DD)\n1DD = (1EE, 1EE)\n1EE = (1FF, 1FF)\n1FF = (1GG, 1GG)\n\
+        1GG = (1EE, 1EE)\n2AA = (2YY, 2YY)\n2YY = (2ZZ, 2ZZ)\n2ZZ = (2YY, 2YY)";
+
+    fn part2(input: &str) -> Option<usize> {
+        let (directions, entries) = parse_network(input.as_bytes());
+        steps_until_all_end(&ghosts(&directions, &entries))
+    }
+
+    #[test]
+    fn ghosts_meet() {
+        let example = "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\n\
+            22A = (22B, XXX)\n22B = (22C, 22C)\n22C = (22Z, 22Z)\n22Z = (22B, 22B)\n\
+            XXX = (XXX, XXX)";
+        assert_eq!(part2(example), Some(6));
+
+        // the second ghost hits its end node one step into a loop of three, so the LCM of the
+        // first hits (2) is wrong
+        let offset = "L\n\n1AA = (1BB, 1BB)\n1BB = (1ZZ, 1ZZ)\n1ZZ = (1BB, 1BB)\n\
+            2AA = (2ZZ, 2ZZ)\n2ZZ = (2BB, 2BB)\n2BB = (2CC, 2CC)\n2CC = (2ZZ, 2ZZ)";
+        assert_eq!(part2(offset), Some(4));
+
+        // one ghost is only ever on an end node at odd steps, the other at even ones
+        let never = "L\n\n1AA = (1ZZ, 1ZZ)\n1ZZ = (1BB, 1BB)\n1BB = (1ZZ, 1ZZ)\n\
+            2AA = (2BB, 2BB)\n2BB = (2ZZ, 2ZZ)\n2ZZ = (2BB, 2BB)";
+        assert_eq!(part2(never), None);
+
+        assert_eq!(part2(TAIL_HIT), None);
+    }
 
-    println!("part 2: {p2}");
+    #[test]
+    fn strongly_connected() {
+        let (_, entries) = parse_network(
+            b"L\n\nAAA = (BBB, BBB)\nBBB = (CCC, AAA)\nCCC = (DDD, DDD)\nDDD = (CCC, EEE)\n\
+            EEE = (EEE, EEE)",
+        );
+        let sizes = components(&entries)
+            .iter()
+            .map(|component| component.len())
+            .sorted()
+            .collect_vec();
+        assert_eq!(sizes, [1, 2, 2]);
+    }
+
+    #[test]
+    fn analyze_report() {
+        let report = analyze(TAIL_HIT.as_bytes(), &[]);
+        assert!(report.contains("  1AA: tail 5, period 3, end hits [3]\n"));
+        assert!(report.contains("  2AA: tail 1, period 2, end hits [2], clean\n"));
+        assert!(report.ends_with("lcm valid: false"));
+        assert!(report.contains("reachability:\n  1AA -> 1ZZ\n  2AA -> 2ZZ\n"));
+    }
+
+    #[test]
+    fn merge_congruences() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((0, 2), (1, 4)), None);
+    }
 }
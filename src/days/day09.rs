@@ -1,45 +1,125 @@
 use itertools::Itertools;
 
+use crate::parse;
+
 fn parse_line(line: &str) -> Vec<i64> {
-    line.split_whitespace()
-        .map(|number| number.parse().unwrap())
-        .collect_vec()
+    parse::numbers(line.as_bytes())
+        .collect::<Option<_>>()
+        .unwrap_or_else(|| panic!("Not a list of numbers: {line}"))
 }
 
-fn part1(history: Vec<i64>) -> i64 {
-    let changes = history
-        .iter()
-        .tuple_windows()
-        .map(|(first, second)| second - first)
-        .collect_vec();
+fn binomial(n: i128, k: i128) -> Option<i128> {
+    // every prefix of the product is itself a binomial coefficient, so the division is exact
+    (0..k).try_fold(1i128, |acc, idx| {
+        Some(acc.checked_mul(n - idx)? / (idx + 1))
+    })
+}
+
+// A history of n values is a polynomial of degree below n, so the value at position x follows
+// from Newton's forward differences written out in terms of the values themselves:
+//
+//     P(x) = sum over i of (-1)^(n - 1 - i) * C(x, i) * C(x - i - 1, n - 1 - i) * value_i
+//
+// Both binomials are updated in place as i grows, so there's no difference table at all.
+// `None` if anything on the way, or the value itself, doesn't fit.
+fn value_after<'a>(history: impl ExactSizeIterator<Item = &'a i64>, steps: usize) -> Option<i64> {
+    let n = history.len() as i128;
+    if steps == 0 {
+        return Some(history.last().copied().unwrap_or(0));
+    }
+    let x = (n - 1).checked_add(steps.try_into().ok()?)?;
+
+    let mut left = 1i128;
+    let mut right = binomial(x - 1, n - 1)?;
+    let mut sum = 0i128;
+    for (idx, value) in history.enumerate() {
+        let idx = idx as i128;
+        let sign = if (n - 1 - idx) % 2 == 0 { 1 } else { -1 };
+        let term = left
+            .checked_mul(right)?
+            .checked_mul(sign * *value as i128)?;
+        sum = sum.checked_add(term)?;
 
-    if changes.iter().all(|change| *change == 0) {
-        return *history.first().unwrap();
+        left = left.checked_mul(x - idx)? / (idx + 1);
+        if idx + 1 < n {
+            right = right.checked_mul(n - 1 - idx)? / (x - idx - 1);
+        }
     }
+    i64::try_from(sum).ok()
+}
 
-    history.iter().last().unwrap() + part1(changes)
+/// The value `steps` positions after the last one, `None` if it overflows.
+fn extrapolate_forward(history: &[i64], steps: usize) -> Option<i64> {
+    value_after(history.iter(), steps)
 }
 
-fn part2(history: Vec<i64>) -> i64 {
-    let changes = history
-        .iter()
-        .tuple_windows()
-        .map(|(first, second)| second - first)
+/// The value `steps` positions before the first one, `None` if it overflows.
+fn extrapolate_backward(history: &[i64], steps: usize) -> Option<i64> {
+    value_after(history.iter().rev(), steps)
+}
+
+fn checked_sum(mut values: impl Iterator<Item = Option<i64>>) -> Option<i64> {
+    values.try_fold(0i64, |acc, value| acc.checked_add(value?))
+}
+
+/// Sums of the values the given number of steps after and before every history.
+pub fn extrapolate(input: &[u8], args: &[String]) -> String {
+    let steps = args
+        .first()
+        .and_then(|steps| parse::number(steps.as_bytes()))
+        .expect("Usage: --extrapolate <steps>");
+    let lines = std::str::from_utf8(input)
+        .unwrap()
+        .lines()
+        .map(parse_line)
         .collect_vec();
 
-    if changes.iter().all(|change| *change == 0) {
-        return *history.first().unwrap();
-    }
+    let show =
+        |sum: Option<i64>| sum.map_or_else(|| "overflows i64".to_owned(), |sum| sum.to_string());
+    let forward = checked_sum(lines.iter().map(|line| extrapolate_forward(line, steps)));
+    let backward = checked_sum(lines.iter().map(|line| extrapolate_backward(line, steps)));
+    format!(
+        "{steps} ahead: {}\n{steps} behind: {}",
+        show(forward),
+        show(backward)
+    )
+}
+
+pub fn main(input: &[u8]) -> String {
+    let input = std::str::from_utf8(input).unwrap();
+    let lines = input.lines().map(parse_line).collect_vec();
+    let p1 = checked_sum(lines.iter().map(|line| extrapolate_forward(line, 1)))
+        .expect("Part 1 overflows i64");
 
-    history.first().unwrap() - part2(changes)
+    let p2 = checked_sum(lines.iter().map(|line| extrapolate_backward(line, 1)))
+        .expect("Part 2 overflows i64");
+    format!("part 1: {p1}\npart 2: {p2}")
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day09");
-    let mut lines = input.lines().map(parse_line).collect_vec();
-    let p1 = lines.clone().drain(..).map(part1).sum::<i64>();
-    println!("part 1: {p1}");
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    let p2 = lines.drain(..).map(part2).sum::<i64>();
-    println!("part 2: {p2}");
+    #[test]
+    fn extrapolate_steps() {
+        assert_eq!(extrapolate_forward(&[0, 3, 6, 9, 12, 15], 1), Some(18));
+        assert_eq!(extrapolate_backward(&[0, 3, 6, 9, 12, 15], 1), Some(-3));
+        assert_eq!(extrapolate_forward(&[10, 13, 16, 21, 30, 45], 1), Some(68));
+        assert_eq!(extrapolate_backward(&[10, 13, 16, 21, 30, 45], 1), Some(5));
+        // squares
+        assert_eq!(extrapolate_forward(&[0, 1, 4, 9], 3), Some(36));
+        assert_eq!(extrapolate_backward(&[0, 1, 4, 9], 2), Some(4));
+        assert_eq!(extrapolate_forward(&[7], 5), Some(7));
+        assert_eq!(extrapolate_forward(&[1, 2], 0), Some(2));
+        // cubes grow past i64 long before the binomials overflow i128
+        assert_eq!(
+            extrapolate_forward(&[0, 1, 8, 27], 2_097_148),
+            Some(2_097_151i64.pow(3))
+        );
+        assert_eq!(extrapolate_forward(&[0, 1, 8, 27], 2_097_149), None);
+        assert_eq!(
+            extrapolate_forward(&[0, 1, 8, 27, 64, 125], usize::MAX),
+            None
+        );
+    }
 }
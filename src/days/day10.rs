@@ -1,17 +1,17 @@
 use itertools::Itertools;
-use std::ops::Index;
+use std::{fmt, ops::Index};
 use Direction::*;
 use Pipe::*;
 
 struct Map(Vec<Vec<u8>>);
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 struct Coords {
     x: usize,
     y: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum Pipe {
     Ground,
     NorthWest,
@@ -22,7 +22,7 @@ enum Pipe {
     WestEast,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum Direction {
     North,
     East,
@@ -31,26 +31,74 @@ enum Direction {
 }
 
 impl Direction {
-    fn enter_pipe(self, pipe: &Pipe) -> Direction {
+    const ALL: [Direction; 4] = [North, South, East, West];
+
+    // the direction the pipe leads on to, if it can be entered going this way at all
+    fn enter_pipe(self, pipe: &Pipe) -> Option<Direction> {
         match (pipe, self) {
-            (NorthSouth, North) => North,
-            (NorthSouth, South) => South,
-            (SouthEast, North) => East,
-            (SouthEast, West) => South,
-            (NorthWest, East) => North,
-            (NorthWest, South) => West,
-            (WestEast, West) => West,
-            (WestEast, East) => East,
-            (NorthEast, South) => East,
-            (NorthEast, West) => North,
-            (SouthWest, North) => West,
-            (SouthWest, East) => South,
-            _ => panic!(),
+            (NorthSouth, North) => Some(North),
+            (NorthSouth, South) => Some(South),
+            (SouthEast, North) => Some(East),
+            (SouthEast, West) => Some(South),
+            (NorthWest, East) => Some(North),
+            (NorthWest, South) => Some(West),
+            (WestEast, West) => Some(West),
+            (WestEast, East) => Some(East),
+            (NorthEast, South) => Some(East),
+            (NorthEast, West) => Some(North),
+            (SouthWest, North) => Some(West),
+            (SouthWest, East) => Some(South),
+            _ => None,
+        }
+    }
+
+    fn opposite(self) -> Direction {
+        match self {
+            North => South,
+            South => North,
+            East => West,
+            West => East,
         }
     }
 }
 
 impl Pipe {
+    // the pipe whose two ends point the given ways
+    fn with_ends(first: Direction, second: Direction) -> Pipe {
+        match (first, second) {
+            (North, South) | (South, North) => NorthSouth,
+            (North, East) | (East, North) => NorthEast,
+            (North, West) | (West, North) => NorthWest,
+            (South, East) | (East, South) => SouthEast,
+            (South, West) | (West, South) => SouthWest,
+            (East, West) | (West, East) => WestEast,
+            _ => Ground,
+        }
+    }
+
+    fn opens_north(&self) -> bool {
+        matches!(self, NorthWest | NorthSouth | NorthEast)
+    }
+
+    // heavy lines for the main loop, light ones for everything else
+    fn glyph(&self, heavy: bool) -> char {
+        match (self, heavy) {
+            (Ground, _) => '.',
+            (NorthWest, false) => '┘',
+            (NorthSouth, false) => '│',
+            (NorthEast, false) => '└',
+            (SouthWest, false) => '┐',
+            (SouthEast, false) => '┌',
+            (WestEast, false) => '─',
+            (NorthWest, true) => '┛',
+            (NorthSouth, true) => '┃',
+            (NorthEast, true) => '┗',
+            (SouthWest, true) => '┓',
+            (SouthEast, true) => '┏',
+            (WestEast, true) => '━',
+        }
+    }
+
     fn is_enterable_from(&self, dir: Direction) -> bool {
         match self {
             NorthWest => dir == South || dir == East,
@@ -65,16 +113,95 @@ impl Pipe {
 }
 
 impl Coords {
-    fn go_direction(&mut self, dir: Direction) {
+    // the neighbouring coordinates, unless that would go below zero
+    fn go_direction(&self, dir: Direction) -> Option<Coords> {
+        let Coords { x, y } = *self;
         match dir {
-            North => self.y = self.y.saturating_sub(1),
-            South => self.y = self.y.saturating_add(1),
-            East => self.x = self.x.saturating_add(1),
-            West => self.x = self.x.saturating_sub(1),
+            North => Some(Coords {
+                x,
+                y: y.checked_sub(1)?,
+            }),
+            South => Some(Coords { x, y: y + 1 }),
+            East => Some(Coords { x: x + 1, y }),
+            West => Some(Coords {
+                x: x.checked_sub(1)?,
+                y,
+            }),
         }
     }
 }
 
+impl fmt::Display for Coords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// Why there's no closed loop through the start.
+#[derive(Debug, PartialEq)]
+enum LoopError {
+    NoStart,
+    // none of the tiles around the start connect to it
+    Unconnected(Coords),
+    // the pipe at `at` leads out of the grid
+    OffGrid {
+        at: Coords,
+        towards: Direction,
+    },
+    // the pipe at `at` leads into a tile which doesn't connect back
+    DeadEnd {
+        at: Coords,
+        towards: Direction,
+        tile: u8,
+    },
+}
+
+impl fmt::Display for LoopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoStart => write!(f, "There's no start tile"),
+            Self::Unconnected(start) => write!(f, "Nothing connects to the start at {start}"),
+            Self::OffGrid { at, towards } => {
+                write!(
+                    f,
+                    "The loop breaks at {at}: it leads {towards:?} out of the grid"
+                )
+            }
+            Self::DeadEnd { at, towards, tile } => write!(
+                f,
+                "The loop breaks at {at}: it leads {towards:?} into {:?}, which doesn't connect",
+                *tile as char
+            ),
+        }
+    }
+}
+
+/// The closed loop of pipes through the start.
+#[derive(Debug)]
+struct Loop {
+    // every tile of the loop in order, starting at the start
+    tiles: Vec<Coords>,
+    // the pipe hidden under the start tile
+    start_pipe: Pipe,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Tile {
+    Loop,
+    Start,
+    // a pipe which isn't part of the loop
+    Junk { inside: bool },
+    Ground { inside: bool },
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Style {
+    Plain,
+    Ansi,
+}
+
+const RESET: &str = "\x1b[0m";
+
 impl TryFrom<u8> for Pipe {
     type Error = &'static str;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
@@ -108,76 +235,245 @@ impl Map {
         )
     }
 
-    fn find_start(&self) -> Coords {
+    fn tile(&self, coords: &Coords) -> Option<u8> {
+        self.0.get(coords.y)?.get(coords.x).copied()
+    }
+
+    fn find_start(&self) -> Option<Coords> {
         for (y, line) in self.0.iter().enumerate() {
             for (x, chr) in line.iter().enumerate() {
                 if *chr == b'S' {
-                    return Coords { x, y };
+                    return Some(Coords { x, y });
                 }
             }
         }
-        panic!("Couldn't find start coordinates!");
+        None
     }
 
-    fn part1(&self) -> usize {
-        let start_coords = self.find_start();
-        let mut steps = 1;
-        let mut curr_cords = start_coords.clone();
-
-        let mut find_first_pipe = || -> Direction {
-            if let Ok(pipe_north) =
-                TryInto::<Pipe>::try_into(self.0[start_coords.y - 1][start_coords.x])
-            {
-                if pipe_north.is_enterable_from(North) {
-                    curr_cords.go_direction(North);
-                    return North.enter_pipe(&pipe_north);
-                }
-            }
-            if let Ok(pipe_south) =
-                TryInto::<Pipe>::try_into(self.0[start_coords.y + 1][start_coords.x])
-            {
-                if pipe_south.is_enterable_from(South) {
-                    curr_cords.go_direction(South);
-                    return South.enter_pipe(&pipe_south);
-                }
-            }
-            if let Ok(pipe_east) =
-                TryInto::<Pipe>::try_into(self.0[start_coords.y][start_coords.x + 1])
-            {
-                if pipe_east.is_enterable_from(East) {
-                    curr_cords.go_direction(East);
-                    return East.enter_pipe(&pipe_east);
-                }
+    // follows the pipes leaving the start `towards` the given direction until they get back to it
+    fn trace(&self, start: Coords, towards: Direction) -> Result<Loop, (usize, LoopError)> {
+        let mut tiles = vec![start];
+        let (mut at, mut dir) = (start, towards);
+        loop {
+            let broken = |tiles: &Vec<Coords>, error| (tiles.len(), error);
+            let next = at
+                .go_direction(dir)
+                .and_then(|next| Some((next, self.tile(&next)?)));
+            let Some((next, tile)) = next else {
+                return Err(broken(&tiles, LoopError::OffGrid { at, towards: dir }));
+            };
+            if tile == b'S' {
+                return Ok(Loop {
+                    tiles,
+                    start_pipe: Pipe::with_ends(towards, dir.opposite()),
+                });
             }
-            if let Ok(pipe_west) =
-                TryInto::<Pipe>::try_into(self.0[start_coords.y][start_coords.x - 1])
-            {
-                if pipe_west.is_enterable_from(West) {
-                    curr_cords.go_direction(West);
-                    return West.enter_pipe(&pipe_west);
+
+            let exit = Pipe::try_from(tile)
+                .ok()
+                .and_then(|pipe| dir.enter_pipe(&pipe));
+            let Some(exit) = exit else {
+                let error = LoopError::DeadEnd {
+                    at,
+                    towards: dir,
+                    tile,
+                };
+                return Err(broken(&tiles, error));
+            };
+            tiles.push(next);
+            (at, dir) = (next, exit);
+        }
+    }
+
+    /// Follows the loop from the start all the way back to it. If it doesn't close, reports
+    /// where the attempt which got the farthest broke off.
+    fn main_loop(&self) -> Result<Loop, LoopError> {
+        let start = self.find_start().ok_or(LoopError::NoStart)?;
+        let connected = Direction::ALL.into_iter().filter(|dir| {
+            start
+                .go_direction(*dir)
+                .and_then(|next| self.tile(&next))
+                .and_then(|tile| Pipe::try_from(tile).ok())
+                .is_some_and(|pipe| pipe.is_enterable_from(*dir))
+        });
+
+        let mut farthest: Option<(usize, LoopError)> = None;
+        for dir in connected {
+            match self.trace(start, dir) {
+                Ok(main_loop) => return Ok(main_loop),
+                Err(broken) => {
+                    if farthest.as_ref().is_none_or(|(len, _)| broken.0 > *len) {
+                        farthest = Some(broken);
+                    }
                 }
             }
-            panic!()
-        };
+        }
+        Err(farthest.map_or(LoopError::Unconnected(start), |(_, error)| error))
+    }
 
-        let mut curr_dir = find_first_pipe();
+    fn pipe_at(&self, coords: &Coords, main_loop: &Loop) -> Pipe {
+        match self[coords] {
+            b'S' => main_loop.start_pipe,
+            tile => Pipe::try_from(tile).unwrap_or(Ground),
+        }
+    }
 
-        loop {
-            curr_cords.go_direction(curr_dir.clone());
-            if let Ok(pipe) = TryInto::<Pipe>::try_into(self[&curr_cords]) {
-                curr_dir = curr_dir.enter_pipe(&pipe);
-                steps += 1;
-            } else {
-                break;
+    /// What every tile is with respect to the loop. Going along a row, every loop pipe with an
+    /// end pointing north crosses the loop, so the tiles after an odd number of them are inside.
+    fn classify(&self, main_loop: &Loop) -> Vec<Vec<Tile>> {
+        let mut tiles = self
+            .0
+            .iter()
+            .map(|line| vec![Tile::Ground { inside: false }; line.len()])
+            .collect_vec();
+        for coords in &main_loop.tiles {
+            tiles[coords.y][coords.x] = Tile::Loop;
+        }
+        let start = main_loop.tiles[0];
+        tiles[start.y][start.x] = Tile::Start;
+
+        for (y, row) in tiles.iter_mut().enumerate() {
+            let mut inside = false;
+            for (x, tile) in row.iter_mut().enumerate() {
+                let pipe = self.pipe_at(&Coords { x, y }, main_loop);
+                *tile = match tile {
+                    Tile::Loop | Tile::Start => {
+                        inside ^= pipe.opens_north();
+                        *tile
+                    }
+                    _ if pipe == Ground => Tile::Ground { inside },
+                    _ => Tile::Junk { inside },
+                };
             }
         }
+        tiles
+    }
 
-        (steps + 2 - 1) / 2
+    /// The grid drawn with box-drawing characters: the loop in heavy lines and everything else
+    /// as `I` or `O` depending on the side of the loop it's on. With ANSI colors the loop is
+    /// highlighted, junk pipes keep their shape in light lines, dimmed, and everything inside is
+    /// green.
+    fn render(&self, main_loop: &Loop, style: Style) -> String {
+        let tiles = self.classify(main_loop);
+        tiles
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                let mut line = String::new();
+                for (x, tile) in row.iter().enumerate() {
+                    let pipe = self.pipe_at(&Coords { x, y }, main_loop);
+                    let (glyph, color) = match (tile, style) {
+                        (Tile::Start, Style::Plain) => ('S', ""),
+                        // without colors a junk pipe's shape can't show which side it's on
+                        (Tile::Junk { inside } | Tile::Ground { inside }, Style::Plain) => {
+                            (if *inside { 'I' } else { 'O' }, "")
+                        }
+                        (Tile::Start, Style::Ansi) => (pipe.glyph(true), "\x1b[1;33m"),
+                        (Tile::Loop, _) => (pipe.glyph(true), "\x1b[1;36m"),
+                        (Tile::Junk { inside: true }, _) => (pipe.glyph(false), "\x1b[2;32m"),
+                        (Tile::Junk { inside: false }, _) => (pipe.glyph(false), "\x1b[2m"),
+                        (Tile::Ground { inside: true }, _) => ('I', "\x1b[1;32m"),
+                        (Tile::Ground { inside: false }, _) => ('O', "\x1b[2m"),
+                    };
+                    match style {
+                        Style::Plain => line.push(glyph),
+                        Style::Ansi => line.push_str(&format!("{color}{glyph}{RESET}")),
+                    }
+                }
+                line
+            })
+            .join("\n")
+    }
+}
+
+/// Draws the grid and its loop to the terminal, or as plain text to the file given.
+pub fn render(input: &[u8], args: &[String]) -> String {
+    let map = Map::from_lines(input.to_vec());
+    let main_loop = map.main_loop().unwrap_or_else(|err| panic!("{err}"));
+    match args {
+        [] => map.render(&main_loop, Style::Ansi),
+        [path] => {
+            std::fs::write(path, map.render(&main_loop, Style::Plain) + "\n")
+                .unwrap_or_else(|err| panic!("Couldn't write {path}: {err}"));
+            format!("written to {path}")
+        }
+        _ => panic!("Usage: --render [output path]"),
     }
 }
 
-pub fn main() {
-    let map = Map::from_lines(include_bytes!("../../input/day10").to_vec());
-    let p1 = map.part1();
-    println!("part 1: {p1}");
+pub fn main(input: &[u8]) -> String {
+    let map = Map::from_lines(input.to_vec());
+    let main_loop = map.main_loop().unwrap_or_else(|err| panic!("{err}"));
+    // the farthest tile is halfway around
+    let p1 = main_loop.tiles.len() / 2;
+    format!("part 1: {p1}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn main_loop(grid: &str) -> Result<Loop, LoopError> {
+        Map::from_lines(grid.as_bytes().to_vec()).main_loop()
+    }
+
+    #[test]
+    fn closed_loops() {
+        let example = "..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...";
+        assert_eq!(main_loop(example).unwrap().tiles.len() / 2, 8);
+        // start in the corner with junk pointing out of the grid around it
+        let corner = "S7|\nLJ.";
+        assert_eq!(main_loop(corner).unwrap().tiles.len(), 4);
+    }
+
+    #[test]
+    fn render_plain() {
+        let grid = "...........\n.S-------7.\n.|F-----7|.\n.||.....||.\n.||.....||.\n\
+            .|L-7.F-J|.\n.|..|.|..|.\n.L--J.L--J.\n...........";
+        let map = Map::from_lines(grid.as_bytes().to_vec());
+        let main_loop = map.main_loop().unwrap();
+        assert_eq!(main_loop.start_pipe, SouthEast);
+        let rendered = map.render(&main_loop, Style::Plain);
+        assert_eq!(rendered.matches('I').count(), 4);
+        assert_eq!(
+            rendered.lines().take(3).join("\n"),
+            "OOOOOOOOOOO\nOS━━━━━━━┓O\nO┃┏━━━━━┓┃O"
+        );
+        assert_eq!(rendered.lines().nth(6).unwrap(), "O┃II┃O┃II┃O");
+
+        // junk pipes inside the loop are marked as well
+        let grid = "FF7FSF7F7F7F7F7F---7\nL|LJ||||||||||||F--J\nFL-7LJLJ||||||LJL-77\n\
+            F--JF--7||LJLJ7F7FJ-\nL---JF-JLJ.||-FJLJJ7\n|F|F-JF---7F7-L7L|7|\n\
+            |FFJF7L7F-JF7|JL---7\n7-L-JL7||F7|L7F-7F7|\nL.L7LFJ|||||FJL7||LJ\n\
+            L7JLJL-JLJLJL--JLJ.L";
+        let map = Map::from_lines(grid.as_bytes().to_vec());
+        let main_loop = map.main_loop().unwrap();
+        let rendered = map.render(&main_loop, Style::Plain);
+        assert_eq!(rendered.matches('I').count(), 10);
+        assert_eq!(rendered.lines().nth(4).unwrap(), "┗━━━┛┏━┛┗┛IIII┏┛┗┛OO");
+    }
+
+    #[test]
+    fn broken_loops() {
+        assert_eq!(main_loop("...\n.F.").unwrap_err(), LoopError::NoStart);
+        assert_eq!(
+            main_loop(".-.\n|S|\n.-.").unwrap_err(),
+            LoopError::Unconnected(Coords { x: 1, y: 1 })
+        );
+        assert_eq!(
+            main_loop("S-7\n|.|\nL-.").unwrap_err(),
+            LoopError::DeadEnd {
+                at: Coords { x: 1, y: 2 },
+                towards: East,
+                tile: b'.'
+            }
+        );
+        assert_eq!(
+            main_loop("S--\n|..\nL--").unwrap_err(),
+            LoopError::OffGrid {
+                at: Coords { x: 2, y: 2 },
+                towards: East
+            }
+        );
+    }
 }
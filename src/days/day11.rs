@@ -1,11 +1,8 @@
-use core::fmt;
-use std::collections::HashMap;
-
 use itertools::Itertools;
 
-struct Map(Vec<Vec<u8>>);
+use crate::parse;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 struct Coords {
     x: usize,
     y: usize,
@@ -13,116 +10,215 @@ struct Coords {
 
 impl Coords {
     fn get_distance(&self, other: &Coords) -> usize {
-        let x = {
-            if self.x > other.x {
-                self.x - other.x
-            } else {
-                other.x - self.x
-            }
-        };
-        let y = {
-            if self.y > other.y {
-                self.y - other.y
-            } else {
-                other.y - self.y
-            }
-        };
-        x + y
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
     }
 }
 
-impl Map {
-    fn new(lines: &[u8]) -> Self {
-        Self(
-            lines
-                .split(|num| *num == b'\n')
-                .map(Vec::from)
-                .collect_vec(),
-        )
-    }
+/// How many lines every empty row and every empty column turns into.
+#[derive(Debug, Clone, Copy)]
+struct Expansion {
+    rows: usize,
+    cols: usize,
+}
 
-    fn get_empty(&self) -> (Vec<usize>, Vec<usize>) {
-        let mut rows = Vec::new();
-        let mut cols = Vec::new();
-        let mut cols_map = HashMap::new();
-
-        for (row_idx, line) in self.0.iter().enumerate().take(self.0.len() - 1) {
-            let mut empty_row = true;
-            for (col_idx, chr) in line.iter().enumerate() {
-                if *chr == b'#' {
-                    empty_row = false;
-                    cols_map.insert(col_idx, true);
-                }
-            }
-            if empty_row {
-                rows.push(row_idx);
-            }
+impl Expansion {
+    fn uniform(factor: usize) -> Self {
+        Self {
+            rows: factor,
+            cols: factor,
         }
+    }
 
-        for idx in 0..(self.0.len() - 1) {
-            if !cols_map.contains_key(&idx) {
-                cols.push(idx);
-            }
+    // either one factor for both axes or `<rows>,<cols>`
+    fn from_arg(arg: &str) -> Option<Self> {
+        match arg.split_once(',') {
+            Some((rows, cols)) => Some(Self {
+                rows: parse::number(rows.as_bytes())?,
+                cols: parse::number(cols.as_bytes())?,
+            }),
+            None => Some(Self::uniform(parse::number(arg.as_bytes())?)),
         }
+    }
+}
 
-        (cols, rows)
+/// Only the galaxies of the image, the empty space is implied by the gaps between them.
+struct Galaxies {
+    // in reading order, before any expansion
+    positions: Vec<Coords>,
+    // every galaxy's coordinate along each axis, sorted
+    xs: Vec<usize>,
+    ys: Vec<usize>,
+}
+
+impl Galaxies {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let positions = bytes
+            .split(|byte| *byte == b'\n')
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.iter()
+                    .positions(|chr| *chr == b'#')
+                    .map(move |x| Coords { x, y })
+            })
+            .collect_vec();
+        // reading order already sorts them by row
+        let ys = positions.iter().map(|galaxy| galaxy.y).collect_vec();
+        let xs = positions
+            .iter()
+            .map(|galaxy| galaxy.x)
+            .sorted_unstable()
+            .collect_vec();
+
+        Self { positions, xs, ys }
     }
 
-    fn get_galaxies_expanded(&self, expand_dist: usize) -> Vec<Coords> {
-        let (cols, rows) = self.get_empty();
-        let mut expand_y = 0;
-        let mut galaxies = Vec::new();
-
-        for (y, line) in self.0.iter().enumerate() {
-            if rows.contains(&y) {
-                expand_y += expand_dist;
-            }
-            let mut expand_x = 0;
-            for (x, char) in line.iter().enumerate() {
-                if cols.contains(&x) {
-                    expand_x += expand_dist;
-                }
-                if *char == b'#' {
-                    galaxies.push(Coords {
-                        x: x + expand_x,
-                        y: y + expand_y,
-                    });
-                }
-            }
-        }
-        galaxies
+    /// Sum of the distances between every pair of galaxies after the expansion.
+    fn distance_sum(&self, expansion: Expansion) -> usize {
+        pairwise_sum(stretch(&self.xs, expansion.cols))
+            + pairwise_sum(stretch(&self.ys, expansion.rows))
+    }
+
+    /// Where the galaxy with the given index ends up after the expansion.
+    fn expanded(&self, idx: usize, expansion: Expansion) -> Option<Coords> {
+        let galaxy = self.positions.get(idx)?;
+        let stretch_one = |sorted: &[usize], coord: usize, factor: usize| {
+            let occupied = sorted
+                .iter()
+                .dedup()
+                .take_while(|other| **other < coord)
+                .count();
+            occupied + (coord - occupied) * factor
+        };
+        Some(Coords {
+            x: stretch_one(&self.xs, galaxy.x, expansion.cols),
+            y: stretch_one(&self.ys, galaxy.y, expansion.rows),
+        })
     }
 }
 
-impl fmt::Display for Map {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut txt = String::new();
-        for line in self.0.iter() {
-            for chr in line {
-                txt.push(*chr as char);
-            }
-            txt.push('\n');
+// Moves sorted coordinates apart so every empty line before them counts `factor` times, which
+// keeps them sorted.
+fn stretch(sorted: &[usize], factor: usize) -> impl Iterator<Item = usize> + '_ {
+    let mut occupied = 0;
+    let mut prev = None;
+    sorted.iter().map(move |coord| {
+        if prev != Some(coord) {
+            occupied += 1;
+            prev = Some(coord);
         }
-        write!(f, "{}", txt)
+        // all lines before this one, less the ones with a galaxy
+        let empty = coord + 1 - occupied;
+        occupied - 1 + empty * factor
+    })
+}
+
+// Sum of the differences between all pairs of sorted values, every value is bigger than all
+// the ones before it so it only has to be compared against their sum.
+fn pairwise_sum(sorted: impl Iterator<Item = usize>) -> usize {
+    let mut prefix = 0;
+    let mut sum = 0;
+    for (idx, value) in sorted.enumerate() {
+        sum += value * idx - prefix;
+        prefix += value;
     }
+    sum
 }
 
-fn solve_for_expand_dist(map: &Map, expand: usize) -> usize {
-    let mut sum: usize = 0;
-    let galaxies = map.get_galaxies_expanded(expand);
+fn parse_expansion(arg: &str) -> Expansion {
+    Expansion::from_arg(arg)
+        .unwrap_or_else(|| panic!("Expansion is <factor> or <rows>,<cols>, got {arg}"))
+}
 
-    for (idx, galaxy1) in galaxies.iter().enumerate() {
-        for galaxy2 in galaxies[(idx + 1)..galaxies.len()].iter() {
-            sum += galaxy1.get_distance(galaxy2);
-        }
+/// Distance sums for every expansion given, either one factor for both axes or `<rows>,<cols>`.
+pub fn expand(input: &[u8], args: &[String]) -> String {
+    let galaxies = Galaxies::from_bytes(input);
+    if args.is_empty() {
+        panic!("Usage: --expand <expansion>...");
     }
-    sum
+    args.iter()
+        .map(|arg| format!("{arg}: {}", galaxies.distance_sum(parse_expansion(arg))))
+        .join("\n")
+}
+
+/// Distance between two galaxies, numbered from 1 in reading order, after the expansion given
+/// or the one of part 1.
+pub fn distance(input: &[u8], args: &[String]) -> String {
+    let usage = "Usage: --distance <galaxy> <galaxy> [expansion]";
+    let (first, second, expansion) = match args {
+        [first, second] => (first, second, Expansion::uniform(2)),
+        [first, second, expansion] => (first, second, parse_expansion(expansion)),
+        _ => panic!("{usage}"),
+    };
+    let galaxies = Galaxies::from_bytes(input);
+    let galaxy = |arg: &String| {
+        parse::number::<usize>(arg.as_bytes())
+            .and_then(|no| galaxies.expanded(no.checked_sub(1)?, expansion))
+            .unwrap_or_else(|| {
+                let count = galaxies.positions.len();
+                panic!("There are galaxies 1 to {count}, got {arg}\n{usage}")
+            })
+    };
+
+    let (first_pos, second_pos) = (galaxy(first), galaxy(second));
+    format!(
+        "galaxy {first} at {first_pos:?}, galaxy {second} at {second_pos:?}\ndistance: {}",
+        first_pos.get_distance(&second_pos)
+    )
 }
 
-pub fn main() {
-    let input = include_bytes!("../../input/day11");
-    let map = Map::new(input);
+pub fn main(input: &[u8]) -> String {
+    let galaxies = Galaxies::from_bytes(input);
 
-    println!("part1: {}", solve_for_expand_dist(&map, 1));
-    println!("part2: {}", solve_for_expand_dist(&map, 999_999));
+    format!(
+        "part1: {}\npart2: {}",
+        galaxies.distance_sum(Expansion::uniform(2)),
+        galaxies.distance_sum(Expansion::uniform(1_000_000))
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"...#......\n.......#..\n#.........\n..........\n......#...\n\
+        .#........\n.........#\n..........\n.......#..\n#...#.....";
+
+    #[test]
+    fn expanded_distances() {
+        let galaxies = Galaxies::from_bytes(EXAMPLE);
+        assert_eq!(galaxies.distance_sum(Expansion::uniform(1)), 292);
+        assert_eq!(galaxies.distance_sum(Expansion::uniform(2)), 374);
+        assert_eq!(galaxies.distance_sum(Expansion::uniform(10)), 1030);
+        assert_eq!(galaxies.distance_sum(Expansion::uniform(100)), 8410);
+
+        // the sum has to agree with summing up every pair separately
+        for (rows, cols) in [(0, 0), (1, 7), (5, 1), (3, 1000)] {
+            let expansion = Expansion { rows, cols };
+            let pairs = (0..galaxies.positions.len())
+                .map(|idx| galaxies.expanded(idx, expansion).unwrap())
+                .tuple_combinations()
+                .map(|(first, second)| first.get_distance(&second))
+                .sum::<usize>();
+            assert_eq!(galaxies.distance_sum(expansion), pairs);
+        }
+    }
+
+    #[test]
+    fn single_galaxies() {
+        let galaxies = Galaxies::from_bytes(EXAMPLE);
+        let distance = |first: usize, second: usize| {
+            let expansion = Expansion::uniform(2);
+            let first = galaxies.expanded(first - 1, expansion).unwrap();
+            first.get_distance(&galaxies.expanded(second - 1, expansion).unwrap())
+        };
+        assert_eq!(distance(5, 9), 9);
+        assert_eq!(distance(1, 7), 15);
+        assert_eq!(distance(3, 6), 17);
+        assert_eq!(distance(8, 9), 5);
+        assert_eq!(
+            galaxies.expanded(0, Expansion { rows: 1, cols: 3 }),
+            Some(Coords { x: 5, y: 0 })
+        );
+        assert_eq!(galaxies.expanded(9, Expansion::uniform(2)), None);
+    }
 }
@@ -1,136 +1,118 @@
 use itertools::Itertools;
 
-trait ByteVal {
-    // returns true when value is a value which a single bit can take in a byte
-    // e.g. 1,2,4,8,16,2048...
-    fn is_byte_val(&self) -> bool;
+use crate::parse;
+
+/// Where a pattern folds onto itself.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Reflection {
+    // mirror below this many rows
+    Horizontal(usize),
+    // mirror right of this many columns
+    Vertical(usize),
 }
-impl ByteVal for usize {
-    fn is_byte_val(&self) -> bool {
-        *self != 0 && (self & (self - 1)) == 0
+
+impl Reflection {
+    fn summary(&self) -> usize {
+        match self {
+            Reflection::Horizontal(rows) => rows * 100,
+            Reflection::Vertical(cols) => *cols,
+        }
     }
 }
 
-fn get_line_byte(values: &&[u8]) -> u32 {
-    values.iter().enumerate().fold(0, |acc, (idx, value)| {
-        if *value == b'#' {
-            acc | (1 << idx)
-        } else {
-            acc
-        }
-    })
+/// Every row and every column of a pattern as a bitmask of its rocks.
+struct Pattern {
+    rows: Vec<u32>,
+    cols: Vec<u32>,
 }
 
-fn get_reflection(hashed: &Vec<u32>) -> Option<usize> {
-    for (mut left, mut right) in (0..hashed.len()).tuple_windows() {
-        let mirror_point = left + 1;
-        while hashed[left] == hashed[right] {
-            if left == 0 || right == (hashed.len() - 1) {
-                return Some(mirror_point);
+impl Pattern {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let lines = bytes.split(|byte| *byte == b'\n').collect_vec();
+        let width = lines.first().map_or(0, |line| line.len());
+        let mut rows = vec![0; lines.len()];
+        let mut cols = vec![0; width];
+        for (y, line) in lines.iter().enumerate() {
+            for (x, _) in line.iter().enumerate().filter(|(_, chr)| **chr == b'#') {
+                rows[y] |= 1 << x;
+                cols[x] |= 1 << y;
             }
-            left -= 1;
-            right += 1;
         }
-    }
-
-    None
-}
 
-fn get_reflection_part_two(hashed: &Vec<u32>) -> Option<usize> {
-    for (mut left, mut right) in (0..hashed.len()).tuple_windows() {
-        let mut smudge = false;
-        let mirror_point = left + 1;
-        loop {
-            if hashed[left] != hashed[right] {
-                if smudge {
-                    break;
-                }
-
-                let diff = {
-                    let left = hashed[left];
-                    let right = hashed[right];
-                    if left > right {
-                        left - right
-                    } else {
-                        right - left
-                    }
-                } as usize;
-
-                if !diff.is_byte_val() {
-                    break;
-                }
-                smudge = true;
-            }
+        Self { rows, cols }
+    }
 
-            if left == 0 || right == (hashed.len() - 1) {
-                if smudge {
-                    return Some(mirror_point);
-                }
-                break;
-            }
-            left -= 1;
-            right += 1;
-        }
+    /// All mirrors which work once exactly `smudges` tiles get flipped.
+    fn reflections(&self, smudges: u32) -> impl Iterator<Item = Reflection> + '_ {
+        let horizontal = mirrors(&self.rows, smudges).map(Reflection::Horizontal);
+        let vertical = mirrors(&self.cols, smudges).map(Reflection::Vertical);
+        horizontal.chain(vertical)
     }
+}
 
-    None
+// Positions between lines where the lines on both sides differ in exactly `smudges` tiles in
+// total, the lines past either edge don't need a counterpart.
+fn mirrors(lines: &[u32], smudges: u32) -> impl Iterator<Item = usize> + '_ {
+    (1..lines.len()).filter(move |mirror| {
+        let before = lines[..*mirror].iter().rev();
+        let after = lines[*mirror..].iter();
+        let differing = before
+            .zip(after)
+            .map(|(above, below)| (above ^ below).count_ones())
+            .sum::<u32>();
+        differing == smudges
+    })
 }
 
-fn hash_vertical(ground: &Vec<&[u8]>) -> Vec<u32> {
-    let mut cols: Vec<Vec<u8>> = vec![Vec::with_capacity(ground.len()); ground[0].len()];
+fn summarize(patterns: &[Pattern], smudges: u32) -> usize {
+    patterns
+        .iter()
+        .flat_map(|pattern| pattern.reflections(smudges))
+        .map(|reflection| reflection.summary())
+        .sum()
+}
 
-    for line in ground {
-        for (idx, val) in line.iter().enumerate() {
-            cols[idx].push(*val);
-        }
-    }
+pub fn main(input: &[u8]) -> String {
+    let patterns = parse::blocks(input).map(Pattern::from_bytes).collect_vec();
 
-    cols.iter()
-        .map(|single| get_line_byte(&single.as_slice()))
-        .collect_vec()
+    let p1 = summarize(&patterns, 0);
+    let p2 = summarize(&patterns, 1);
+    format!("part1: {p1}\npart2: {p2}")
 }
 
-pub fn main() {
-    let patterns = include_str!("../../input/day13")
-        .split("\n\n")
-        .collect_vec();
-
-    fn ground_bytes<'a>(ground: &'a &str) -> Vec<&'a [u8]> {
-        ground
-            .as_bytes()
-            .split(|x| *x == b'\n')
-            .take_while(|v| !v.is_empty())
-            .collect_vec()
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &[u8] = b"#.##..##.\n..#.##.#.\n##......#\n##......#\n..#.##.#.\n..##..##.\n\
+        #.#.##.#.\n\n#...##..#\n#....#..#\n..##..###\n#####.##.\n#####.##.\n..##..###\n#....#..#";
+
+    #[test]
+    fn smudged_mirrors() {
+        let patterns = parse::blocks(EXAMPLE)
+            .map(Pattern::from_bytes)
+            .collect_vec();
+        let reflections = |smudges| {
+            patterns
+                .iter()
+                .map(|pattern| pattern.reflections(smudges).collect_vec())
+                .collect_vec()
+        };
+        assert_eq!(
+            reflections(0),
+            [
+                vec![Reflection::Vertical(5)],
+                vec![Reflection::Horizontal(4)]
+            ]
+        );
+        assert_eq!(
+            reflections(1),
+            [
+                vec![Reflection::Horizontal(3)],
+                vec![Reflection::Horizontal(1)]
+            ]
+        );
+        assert_eq!(summarize(&patterns, 0), 405);
+        assert_eq!(summarize(&patterns, 1), 400);
     }
-
-    let p1 = patterns.iter().fold(0, |acc, ground| {
-        let ground = ground_bytes(ground);
-        let hashed_horizontal = ground.iter().map(get_line_byte).collect_vec();
-        if let Some(val) = get_reflection(&hashed_horizontal) {
-            return acc + val * 100;
-        }
-        let hashed_vertical = hash_vertical(&ground);
-        if let Some(val) = get_reflection(&hashed_vertical) {
-            return acc + val;
-        }
-        acc
-    });
-
-    println!("part1: {p1}");
-
-    let p2 = patterns.iter().fold(0, |acc, ground| {
-        let ground = ground_bytes(ground);
-        // Vertical has priority for some reason and in part 2 both vertical and horizontal can be
-        // true so evaluating vertical first is a must here
-        let hashed_vertical = hash_vertical(&ground);
-        if let Some(val) = get_reflection_part_two(&hashed_vertical) {
-            return acc + val;
-        }
-        let hashed_horizontal = ground.iter().map(get_line_byte).collect_vec();
-        if let Some(val) = get_reflection_part_two(&hashed_horizontal) {
-            return acc + val * 100;
-        }
-        acc
-    });
-    println!("part2: {p2}");
 }
@@ -1,21 +1,26 @@
 use std::cmp::Reverse;
 
-use indexmap::IndexMap;
 use itertools::Itertools;
 
-#[derive(Debug, Clone)]
+use crate::cycle;
+
+const SPIN_CYCLES: usize = 1_000_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
 struct Coords {
     x: usize,
     y: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 enum Rock {
     Round,
     Cube,
 }
 
-#[derive(Debug, Clone)]
+// After a full cycle the rocks end up sorted by their coordinates, so comparing the vectors
+// compares the layouts
+#[derive(Debug, Clone, PartialEq)]
 struct Rocks {
     rocks: Vec<(Coords, Rock)>,
     bounds: usize,
@@ -33,7 +38,7 @@ impl Rocks {
                 };
             }
         }
-        let bounds = bytes.split(|byte| *byte == b'\n').count() - 1;
+        let bounds = bytes.split(|byte| *byte == b'\n').count();
 
         Self { rocks, bounds }
     }
@@ -137,16 +142,7 @@ impl Rocks {
     }
 }
 
-impl Iterator for Rocks {
-    type Item = usize;
-    fn next(&mut self) -> Option<Self::Item> {
-        self.cycle();
-        Some(self.get_north_load())
-    }
-}
-
-pub fn main() {
-    let input = &include_bytes!("../../input/day14")[..];
+pub fn main(input: &[u8]) -> String {
     let rocks = Rocks::from_bytes(&input);
 
     let p1 = {
@@ -154,39 +150,14 @@ pub fn main() {
         rocks.shift_north();
         rocks.get_north_load()
     };
-    println!("part 1: {p1}");
 
     let p2 = {
-        let rocks = rocks.clone();
-        let loads = rocks.into_iter().cycle().take(200).collect_vec();
-
-        let first_cycle_val = {
-            let mut load_frequency: IndexMap<usize, u8> = IndexMap::new();
-
-            for load in loads.iter() {
-                load_frequency
-                    .entry(*load)
-                    .and_modify(|e| *e += 1)
-                    .or_insert(1);
-            }
-
-            load_frequency
-                .iter()
-                .find(|(_, freq)| **freq > 3)
-                .unwrap()
-                .0
-                .to_owned()
+        let spin = |rocks: &Rocks| {
+            let mut rocks = rocks.clone();
+            rocks.cycle();
+            rocks
         };
-
-        let (cycle_start, cycle_end) = loads
-            .iter()
-            .positions(|load| *load == first_cycle_val)
-            .take(2)
-            .collect_tuple()
-            .unwrap();
-
-        let cycle = &loads[cycle_start..cycle_end];
-        cycle[(1000000000 - (cycle_start + 1)) % cycle.len()]
+        cycle::nth(rocks, spin, SPIN_CYCLES).get_north_load()
     };
-    println!("part 2: {p2}");
+    format!("part 1: {p1}\npart 2: {p2}")
 }
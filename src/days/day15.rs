@@ -3,6 +3,8 @@ use itertools::Itertools;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+use crate::parse;
+
 #[derive(Debug)]
 enum Operation {
     Remove,
@@ -10,17 +12,17 @@ enum Operation {
 }
 
 #[derive(Debug)]
-struct Step {
-    label: &'static [u8],
+struct Step<'a> {
+    label: &'a [u8],
     operation: Operation,
 }
 
-impl Step {
-    fn from_seq(seq: &&'static [u8]) -> Self {
-        if seq.iter().last().unwrap().is_ascii_digit() {
+impl<'a> Step<'a> {
+    fn from_seq(seq: &&'a [u8]) -> Self {
+        if let Some((label, focal)) = parse::split_once(seq, b'=') {
             Self {
-                label: &seq[0..seq.len() - 2],
-                operation: Operation::Replace(*seq.iter().last().unwrap() - 48),
+                label,
+                operation: Operation::Replace(parse::number(focal).unwrap()),
             }
         } else {
             Self {
@@ -49,7 +51,7 @@ fn calculate_hash<T: Hash>(t: &T) -> usize {
     s.finish() as usize
 }
 
-fn part2(steps: Vec<&'static [u8]>) -> usize {
+fn part2(steps: Vec<&[u8]>) -> usize {
     // cannot do [IndexMap::new(); 256], bcs IndexMap doesn't implement std::Marker::Copy
     let mut boxes: [IndexMap<usize, u8>; 256] = (0..256)
         .map(|_| IndexMap::new())
@@ -85,15 +87,11 @@ fn part2(steps: Vec<&'static [u8]>) -> usize {
     sum
 }
 
-pub fn main() {
-    let input = include_bytes!("../../input/day15");
-    let input: Vec<&[u8]> = input[0..input.len() - 1]
-        .split(|s| *s == b',')
-        .collect_vec();
+pub fn main(input: &[u8]) -> String {
+    let input: Vec<&[u8]> = input.split(|s| *s == b',').collect_vec();
 
     let p1 = input.iter().map(get_hash).sum::<usize>();
-    println!("part 1: {p1}");
 
     let p2 = part2(input);
-    println!("part 2: {p2}");
+    format!("part 1: {p1}\npart 2: {p2}")
 }
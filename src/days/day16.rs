@@ -206,7 +206,7 @@ impl Area {
 
         let (max_x, max_y) = {
             let mut split = bytes.split(|byte| *byte == b'\n');
-            let max_y = split.clone().count() - 1;
+            let max_y = split.clone().count();
             let max_x = split.next().map(|arr| arr.len()).unwrap_or(0);
             (max_x, max_y)
         };
@@ -278,8 +278,7 @@ fn longest_edge_path(area: &Area) -> usize {
     max_path
 }
 
-pub fn main() {
-    let input = include_bytes!("../../input/day16");
+pub fn main(input: &[u8]) -> String {
     let area = Area::from_bytes(input);
     let p1 = path_length(
         area.clone(),
@@ -289,8 +288,7 @@ pub fn main() {
             delete: false,
         },
     );
-    println!("part1 : {p1}");
 
     let p2 = longest_edge_path(&area);
-    println!("part2 : {p2}");
+    format!("part1 : {p1}\npart2 : {p2}")
 }
@@ -1,32 +1,8 @@
-use std::{collections::HashMap, ops::Sub};
+use std::collections::HashMap;
 
 use itertools::Itertools;
 
-trait ToNum {
-    fn to_number(&self) -> u32;
-}
-
-impl ToNum for &[u8] {
-    fn to_number(&self) -> u32 {
-        let mut number = 0u32;
-        for byte in self.iter() {
-            number *= 10;
-            number += byte.sub(48) as u32;
-        }
-        number
-    }
-}
-
-impl ToNum for &[&u8] {
-    fn to_number(&self) -> u32 {
-        let mut number = 0u32;
-        for byte in self.iter() {
-            number *= 10;
-            number += byte.sub(48) as u32;
-        }
-        number
-    }
-}
+use crate::parse;
 
 #[derive(Debug)]
 struct Part {
@@ -47,10 +23,10 @@ impl Part {
         };
 
         Some(Self {
-            x: (&x[2..]).to_number(),
-            m: (&m[2..]).to_number(),
-            a: (&a[2..]).to_number(),
-            s: (&s[2..]).to_number(),
+            x: parse::number(&x[2..])?,
+            m: parse::number(&m[2..])?,
+            a: parse::number(&a[2..])?,
+            s: parse::number(&s[2..])?,
         })
     }
 
@@ -109,12 +85,9 @@ impl Rule {
                 false
             }
         };
-        let value = it
-            .take_while(|byte| **byte != b':')
-            .collect_vec()
-            .as_slice()
-            .to_number();
-        let accept = RuleResult::from_bytes(bytes.split(|byte| *byte == b':').last().unwrap());
+        let (value, accept) = parse::split_once(it.as_slice(), b':').unwrap();
+        let value = parse::number(value).unwrap();
+        let accept = RuleResult::from_bytes(accept);
 
         Self {
             symbol,
@@ -185,8 +158,8 @@ fn solve_part1(parts: &[Part], workflows: &HashMap<String, Workflow>) -> usize {
     })
 }
 
-pub fn main() {
-    let input = include_str!("../../input/day19");
+pub fn main(input: &[u8]) -> String {
+    let input = std::str::from_utf8(input).unwrap();
     let (workflows, parts) = input.split_once("\n\n").unwrap();
     let parts = parts
         .as_bytes()
@@ -217,5 +190,5 @@ pub fn main() {
         .collect();
 
     let p1 = solve_part1(&parts, &workflows);
-    println!("part1: {p1}");
+    format!("part1: {p1}")
 }
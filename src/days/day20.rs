@@ -194,7 +194,7 @@ impl Machine {
         let mut conj_parent_idxs = HashMap::<usize, Vec<usize>>::new();
 
         // line number + rx module
-        let module_no = bytes.iter().filter(|byte| **byte == b'\n').count() + 1;
+        let module_no = bytes.split(|byte| *byte == b'\n').count() + 1;
         let mut modules = Vec::<Module>::with_capacity(module_no);
         #[allow(clippy::uninit_vec)]
         unsafe {
@@ -289,11 +289,10 @@ impl Machine {
     }
 }
 
-pub fn main() {
-    let machine = Machine::from_bytes(include_bytes!("../../input/day20"));
+pub fn main(input: &[u8]) -> String {
+    let machine = Machine::from_bytes(input);
     let p1 = machine.clone().solve_part1();
-    println!("part 1: {p1}");
 
     let p2 = machine.clone().solve_part2();
-    println!("part 2: {p2}");
+    format!("part 1: {p1}\npart 2: {p2}")
 }
@@ -18,7 +18,7 @@ struct Map {
 
 impl Map {
     fn from_bytes(bytes: &[u8]) -> Self {
-        let row_len = bytes.split(|byte| *byte == b'\n').count() - 1;
+        let row_len = bytes.split(|byte| *byte == b'\n').count();
         let occupied = vec![false; row_len * row_len];
         let mut map = Self { occupied, row_len };
 
@@ -129,11 +129,10 @@ fn solve_part1(map: &Map, start: Coords) -> usize {
     possible.len()
 }
 
-pub fn main() {
-    let input = include_bytes!("../../input/day21");
+pub fn main(input: &[u8]) -> String {
     let map = Map::from_bytes(input);
     let start = find_start(input);
 
     let p1 = solve_part1(&map, start);
-    println!("part1: {p1}");
+    format!("part1: {p1}")
 }
@@ -16,3 +16,58 @@ pub mod day16;
 pub mod day19;
 pub mod day20;
 pub mod day21;
+
+pub type Solver = fn(&[u8]) -> String;
+
+pub const SOLVERS: [(u8, Solver); 18] = [
+    (1, day01::main),
+    (2, day02::main),
+    (3, day03::main),
+    (4, day04::main),
+    (5, day05::main),
+    (6, day06::main),
+    (7, day07::main),
+    (8, day08::main),
+    (9, day09::main),
+    (10, day10::main),
+    (11, day11::main),
+    (13, day13::main),
+    (14, day14::main),
+    (15, day15::main),
+    (16, day16::main),
+    (19, day19::main),
+    (20, day20::main),
+    (21, day21::main),
+];
+
+/// Extra ways to look at a day besides solving it, called with the input and the arguments
+/// following the mode name.
+pub type Mode = fn(&[u8], &[String]) -> String;
+
+pub const MODES: [(u8, &str, Mode); 11] = [
+    (1, "diagnose", day01::diagnose),
+    (2, "feasible", day02::feasible),
+    (5, "seeds-for", day05::seeds_for),
+    (5, "convert", day05::convert),
+    (7, "ruleset", day07::ruleset),
+    (7, "explain", day07::explain),
+    (8, "analyze", day08::analyze),
+    (9, "extrapolate", day09::extrapolate),
+    (10, "render", day10::render),
+    (11, "expand", day11::expand),
+    (11, "distance", day11::distance),
+];
+
+pub fn solver(day: u8) -> Option<Solver> {
+    SOLVERS
+        .iter()
+        .find(|(solved_day, _)| *solved_day == day)
+        .map(|(_, solver)| *solver)
+}
+
+pub fn mode(day: u8, name: &str) -> Option<Mode> {
+    MODES
+        .iter()
+        .find(|(mode_day, mode_name, _)| *mode_day == day && *mode_name == name)
+        .map(|(_, _, mode)| *mode)
+}
@@ -0,0 +1,71 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+/// Passing this as the input path reads the puzzle input from stdin instead.
+pub const STDIN: &str = "-";
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+pub fn default_path(day: u8) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "input", &format!("day{day:02}")]
+        .iter()
+        .collect()
+}
+
+fn read_stdin() -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    io::stdin().lock().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Brings the input into the shape every day expects: no BOM, `\n` line endings and no trailing
+/// newline or blank lines after the last line.
+pub fn normalize(bytes: &[u8]) -> Vec<u8> {
+    let bytes = bytes.strip_prefix(BOM).unwrap_or(bytes);
+    let mut normalized = Vec::with_capacity(bytes.len());
+    for (idx, byte) in bytes.iter().enumerate() {
+        if *byte != b'\r' || bytes.get(idx + 1) != Some(&b'\n') {
+            normalized.push(*byte);
+        }
+    }
+
+    // cut at the end of the last line which has anything in it
+    let len = match normalized
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+    {
+        Some(last) => normalized[last..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(normalized.len(), |newline| last + newline),
+        None => 0,
+    };
+    normalized.truncate(len);
+    normalized
+}
+
+pub fn read(day: u8, path: Option<&str>) -> io::Result<Vec<u8>> {
+    let bytes = match path {
+        Some(STDIN) => read_stdin(),
+        Some(path) => fs::read(path),
+        None => fs::read(default_path(day)),
+    }?;
+    Ok(normalize(&bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalize_input() {
+        assert_eq!(normalize(b"ab\ncd\n"), b"ab\ncd");
+        assert_eq!(normalize(b"\xEF\xBB\xBFab\r\ncd\r\n\r\n  \n"), b"ab\ncd");
+        assert_eq!(normalize(b"ab\n\ncd \n"), b"ab\n\ncd ");
+        assert_eq!(normalize(b"a\rb"), b"a\rb");
+        assert_eq!(normalize(b"\n\n"), b"");
+    }
+}
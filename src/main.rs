@@ -1,39 +1,122 @@
-use std::{env, time::Instant};
+use std::{
+    collections::BTreeMap,
+    env, io,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
+mod cycle;
 mod days;
-use days::{
-    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day13, day14,
-    day15, day16, day19, day20, day21,
-};
+mod input;
+mod parse;
+use days::{Solver, SOLVERS};
+
+const USAGE: &str =
+    "Usage: cargo r -- <day> [input path, - for stdin] [--<mode> [mode args]] | all [--sequential]";
+
+fn solve_default_input(day: u8, solver: Solver) -> io::Result<String> {
+    Ok(solver(&input::read(day, None)?))
+}
+
+fn print_day(day: u8, output: &io::Result<String>, elapsed: Option<Duration>) {
+    match output {
+        Ok(output) => println!("day {day}:\n{output}"),
+        Err(err) => println!("day {day}: couldn't read input: {err}"),
+    }
+    if let Some(elapsed) = elapsed {
+        println!("elapsed: {}s", elapsed.as_secs_f64());
+    }
+}
+
+// Days run one after another, so every day gets the whole machine and its timing is accurate
+fn run_sequential(solvers: &[(u8, Solver)]) {
+    for (day, solver) in solvers {
+        let time = Instant::now();
+        let output = solve_default_input(*day, *solver);
+        print_day(*day, &output, Some(time.elapsed()));
+    }
+}
+
+// Workers pick the next unsolved day until none are left. Results arrive in any order, so they
+// are buffered until every earlier day has been printed
+fn run_parallel(solvers: &[(u8, Solver)]) {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(solvers.len());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                let mut idx = next.fetch_add(1, Ordering::Relaxed);
+                while let Some((day, solver)) = solvers.get(idx) {
+                    sender
+                        .send((idx, solve_default_input(*day, *solver)))
+                        .unwrap();
+                    idx = next.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut to_print = 0;
+        for (idx, output) in receiver {
+            pending.insert(idx, output);
+            while let Some(output) = pending.remove(&to_print) {
+                print_day(solvers[to_print].0, &output, None);
+                to_print += 1;
+            }
+        }
+    });
+}
+
+/// A solver or a mode with its arguments, ready to be given the input.
+type Run<'a> = Box<dyn Fn(&[u8]) -> String + 'a>;
 
 fn main() {
-    let day = env::args()
-        .nth(1)
-        .expect("Usage: cargo r -- <day>")
-        .parse::<u8>()
-        .unwrap_or(0);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (day, args) = args.split_first().expect(USAGE);
 
     let time = Instant::now();
-    match day {
-        1 => day01::main(),
-        2 => day02::main(),
-        3 => day03::main(),
-        4 => day04::main(),
-        5 => day05::main(),
-        6 => day06::main(),
-        7 => day07::main(),
-        8 => day08::main(),
-        9 => day09::main(),
-        10 => day10::main(),
-        11 => day11::main(),
-        13 => day13::main(),
-        14 => day14::main(),
-        15 => day15::main(),
-        16 => day16::main(),
-        19 => day19::main(),
-        20 => day20::main(),
-        21 => day21::main(),
-        _ => unimplemented!("No more day for now!"),
-    };
+    if day == "all" {
+        match args {
+            [] => run_parallel(&SOLVERS),
+            [flag] if flag == "--sequential" => run_sequential(&SOLVERS),
+            _ => panic!("{USAGE}"),
+        }
+    } else {
+        let day = day.parse::<u8>().expect(USAGE);
+        // the input path is optional, so anything starting with -- is already the mode
+        let (path, args) = match args.split_first() {
+            Some((path, args)) if !path.starts_with("--") => (Some(path.as_str()), args),
+            _ => (None, args),
+        };
+        // a missing day or mode says so, rather than complaining about its input
+        let run: Run = match args.split_first() {
+            None => {
+                let solver =
+                    days::solver(day).unwrap_or_else(|| unimplemented!("No more day for now!"));
+                Box::new(solver)
+            }
+            Some((mode, args)) => {
+                let mode = mode
+                    .strip_prefix("--")
+                    .and_then(|mode| days::mode(day, mode))
+                    .unwrap_or_else(|| panic!("Day {day} has no {mode} mode"));
+                Box::new(move |input| mode(input, args))
+            }
+        };
+        let input = input::read(day, path).expect("Couldn't read the input");
+        println!("{}", run(&input));
+    }
     println!("elapsed: {}s", time.elapsed().as_secs_f64());
 }
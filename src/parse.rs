@@ -0,0 +1,159 @@
+//! Small zero-copy parsing helpers shared by the days.
+//!
+//! Everything here works directly on `&[u8]` and hands out sub-slices of the input, so nothing
+//! gets allocated while parsing.
+
+/// Integer types which can be built up from ASCII digits.
+pub trait Number: Copy {
+    const ZERO: Self;
+
+    /// Appends a decimal digit, going downwards for negative numbers. `None` on overflow.
+    fn push_digit(self, digit: u8, negative: bool) -> Option<Self>;
+}
+
+macro_rules! impl_number {
+    ($($ty:ty),*) => {$(
+        impl Number for $ty {
+            const ZERO: Self = 0;
+
+            fn push_digit(self, digit: u8, negative: bool) -> Option<Self> {
+                let shifted = self.checked_mul(10)?;
+                if negative {
+                    shifted.checked_sub(digit as $ty)
+                } else {
+                    shifted.checked_add(digit as $ty)
+                }
+            }
+        }
+    )*};
+}
+
+impl_number!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+fn digit(byte: u8) -> Option<u8> {
+    byte.is_ascii_digit().then(|| byte - b'0')
+}
+
+/// Parses the whole slice as a number with an optional sign. `None` if it contains anything
+/// else or doesn't fit in `T`.
+pub fn number<T: Number>(bytes: &[u8]) -> Option<T> {
+    let (negative, digits) = match bytes {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] => (false, rest),
+        _ => (false, bytes),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    // accumulating towards the sign lets the minimum value of signed types through
+    digits
+        .iter()
+        .try_fold(T::ZERO, |acc, byte| acc.push_digit(digit(*byte)?, negative))
+}
+
+/// Concatenates every digit in `bytes` into one number, ignoring everything else.
+pub fn digits<T: Number>(bytes: &[u8]) -> Option<T> {
+    bytes
+        .iter()
+        .filter_map(|byte| digit(*byte))
+        .try_fold(T::ZERO, |acc, digit| acc.push_digit(digit, false))
+}
+
+/// Splits on ASCII whitespace, skipping empty pieces.
+pub fn words(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    bytes
+        .split(|byte| byte.is_ascii_whitespace())
+        .filter(|word| !word.is_empty())
+}
+
+/// Every whitespace separated word as a number, `None` for words which aren't one. Labels have
+/// to be split off first, e.g. with [`key_value`], and collecting into an `Option` rejects
+/// anything malformed.
+pub fn numbers<'a, T: Number + 'a>(bytes: &'a [u8]) -> impl Iterator<Item = Option<T>> + 'a {
+    words(bytes).map(number)
+}
+
+/// Splits `bytes` at the first occurrence of `separator`.
+pub fn split_once(bytes: &[u8], separator: u8) -> Option<(&[u8], &[u8])> {
+    let idx = bytes.iter().position(|byte| *byte == separator)?;
+    Some((&bytes[..idx], &bytes[(idx + 1)..]))
+}
+
+/// Splits a `key: value` line, trimming whitespace around both parts.
+pub fn key_value(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (key, value) = split_once(bytes, b':')?;
+    Some((key.trim_ascii(), value.trim_ascii()))
+}
+
+/// Iterates over blocks of lines separated by one or more blank lines.
+pub fn blocks(bytes: &[u8]) -> Blocks<'_> {
+    Blocks { rest: bytes }
+}
+
+pub struct Blocks<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.rest.iter().position(|byte| *byte != b'\n')?;
+        let rest = &self.rest[start..];
+
+        match rest.windows(2).position(|window| window == b"\n\n") {
+            Some(end) => {
+                self.rest = &rest[(end + 2)..];
+                Some(&rest[..end])
+            }
+            None => {
+                self.rest = &[];
+                Some(rest.strip_suffix(b"\n").unwrap_or(rest))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(number::<u32>(b"1642"), Some(1642));
+        assert_eq!(number::<u32>(b""), None);
+        assert_eq!(number::<u32>(b"12a"), None);
+        assert_eq!(number::<u8>(b"256"), None);
+        assert_eq!(number::<i64>(b"-35"), Some(-35));
+        assert_eq!(number::<i8>(b"-128"), Some(i8::MIN));
+        assert_eq!(number::<i8>(b"-"), None);
+    }
+
+    #[test]
+    fn parse_lists() {
+        let line = b"       59     79  65";
+        assert_eq!(
+            numbers::<u32>(line).collect::<Option<Vec<_>>>(),
+            Some(vec![59, 79, 65])
+        );
+        assert_eq!(
+            numbers::<u32>(b"59 7x9 65").collect::<Option<Vec<_>>>(),
+            None
+        );
+        assert_eq!(
+            numbers::<u32>(b"Time: 59").collect::<Option<Vec<_>>>(),
+            None
+        );
+        assert_eq!(
+            key_value(b"Game 1: 7 blue"),
+            Some((&b"Game 1"[..], &b"7 blue"[..]))
+        );
+    }
+
+    #[test]
+    fn parse_blocks() {
+        let input = b"a\nb\n\nc\n\n\nd\n";
+        let blocks = blocks(input).collect::<Vec<_>>();
+        assert_eq!(blocks, vec![&b"a\nb"[..], b"c", b"d"]);
+    }
+}